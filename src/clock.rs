@@ -0,0 +1,63 @@
+use core::time::Duration;
+use std::time::Instant;
+
+/// The clock the timers run on, sped up with the hidden `--speed` option so that long timers can
+/// be tested end to end in a few seconds.
+///
+/// The instants it returns are on its own, faster time line, starting from when it was created.
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    epoch: Instant,
+    speed: u32,
+}
+
+impl Clock {
+    /// A clock running `speed` times faster than the real one.
+    pub fn new(speed: u32) -> Self {
+        Self {
+            epoch: Instant::now(),
+            speed: speed.max(1),
+        }
+    }
+
+    pub fn now(self) -> Instant {
+        self.epoch + self.epoch.elapsed().saturating_mul(self.speed)
+    }
+
+    /// The real instant at which the clock shows `instant`, for scheduling with Tokio.
+    pub fn real(self, instant: Instant) -> tokio::time::Instant {
+        (self.epoch + self.real_duration(instant.saturating_duration_since(self.epoch))).into()
+    }
+
+    /// How long `duration` on the clock takes in real time.
+    pub fn real_duration(self, duration: Duration) -> Duration {
+        duration / self.speed
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_time() {
+        let clock = Clock::new(60);
+        let now = clock.now();
+        assert!(now >= clock.epoch);
+        assert_eq!(
+            clock.real_duration(Duration::from_secs(3600)),
+            Duration::from_secs(60)
+        );
+        let minute_later = clock.epoch + Duration::from_secs(60);
+        assert_eq!(
+            clock.real(minute_later),
+            (clock.epoch + Duration::from_secs(1)).into()
+        );
+    }
+}
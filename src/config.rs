@@ -0,0 +1,117 @@
+use std::{ffi::OsString, fs, io, path::PathBuf};
+
+use clap::Command;
+use human_errors::{Error, user, user_with_cause, user_with_internal};
+
+const CONFIG_ADVICE: &str =
+    "Set options by their long name, like `format = \"{remaining}\"` or `bell = false`";
+
+/// Turns the config file into command-line arguments, to be placed before the actual ones so
+/// that those take precedence.
+///
+/// The config file lives in the user's config directory, like `~/.config/timer-cli/config.toml`,
+/// and a missing one is the same as an empty one.
+pub fn load_args(command: &Command) -> Result<Vec<OsString>, Error> {
+    let Some(path) = config_path() else {
+        return Ok(Vec::new());
+    };
+    match fs::read_to_string(&path) {
+        Ok(contents) => args_from_toml(&contents, command).map_err(|err| {
+            user_with_cause(
+                &format!("Failed to load the config file {}", path.display()),
+                "Fix or remove the config file",
+                err,
+            )
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(user_with_internal(
+            &format!("Failed to read the config file {}", path.display()),
+            "Make sure the file is readable",
+            err,
+        )),
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "timer-cli")
+        .map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+fn args_from_toml(contents: &str, command: &Command) -> Result<Vec<OsString>, Error> {
+    let table = contents.parse::<toml::Table>().map_err(|err| {
+        user_with_internal("The config file is not valid TOML", CONFIG_ADVICE, err)
+    })?;
+
+    let mut args = Vec::new();
+    for (key, value) in table {
+        let long = key.replace('_', "-");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+        else {
+            return Err(user(
+                &format!("Unknown option \"{key}\" in the config file"),
+                CONFIG_ADVICE,
+            ));
+        };
+        let value = match value {
+            toml::Value::String(value) => value,
+            toml::Value::Integer(value) => value.to_string(),
+            toml::Value::Float(value) => value.to_string(),
+            // Flags like `--count-up` can only be turned on
+            toml::Value::Boolean(value) if !arg.get_action().takes_values() => {
+                if value {
+                    args.push(OsString::from(format!("--{long}")));
+                }
+                continue;
+            }
+            toml::Value::Boolean(value) => value.to_string(),
+            _ => {
+                return Err(user(
+                    &format!("Unsupported value for \"{key}\" in the config file"),
+                    "Use a string, a number or a boolean",
+                ));
+            }
+        };
+        args.push(OsString::from(format!("--{long}={value}")));
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::Args;
+
+    #[test]
+    fn convert_config_to_args() {
+        let args = args_from_toml(
+            "bell = false\nbell_count = 3\nformat = \"{remaining}\"\nset-title = true\ncount_up = false\n",
+            &Args::command(),
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            [
+                "--bell=false",
+                "--bell-count=3",
+                "--format={remaining}",
+                "--set-title"
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_invalid_config() {
+        for input in ["bell = ", "no_such_option = 1", "format = [1, 2]"] {
+            assert!(
+                args_from_toml(input, &Args::command())
+                    .unwrap_err()
+                    .is_user(),
+                "{input}"
+            );
+        }
+    }
+}
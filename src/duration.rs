@@ -0,0 +1,847 @@
+use core::{str::FromStr, time::Duration};
+
+use human_errors::{Error, user, user_with_cause, user_with_internal};
+
+/// A duration parsed with [`parse_duration`], for use as a command line argument.
+#[derive(Debug, Clone, Copy)]
+pub struct ColonSeparatedDuration(pub Duration);
+
+impl FromStr for ColonSeparatedDuration {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s).map(Self)
+    }
+}
+
+/// Parses a duration in one of the supported formats:
+///
+/// - colon-separated parts, "[[[[w:]d:]h:]m:]s" (e.g., "1:30" for 90 seconds), where any part
+///   may have a fraction after a dot or a comma (e.g., "90.5" or "1.5:0" for 90.5 and 90 seconds);
+/// - number and unit pairs (e.g., "1h30m15s" or "1h 30m"), where the numbers may have a fraction
+///   (e.g., "1.5h");
+/// - ISO 8601 (e.g., "PT1H30M15S");
+/// - numbers followed by unit words (e.g., "5 minutes" or "1 hour 30 min"), see [`WORD_UNITS`].
+///
+/// Fractions are kept to millisecond precision, truncating anything finer.
+///
+/// Surrounding whitespace is ignored. Negative parts and durations too large for a [`Duration`]
+/// are rejected with a user error, like any other malformed input.
+pub fn parse_duration(duration_str: &str) -> Result<Duration, Error> {
+    let duration_str = duration_str.trim();
+    if duration_str.starts_with('P') {
+        return parse_iso8601_duration(duration_str);
+    }
+    // Any word longer than a unit letter, like "min" or "hours"
+    if duration_str
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .any(|word| word.len() > 1)
+    {
+        return parse_word_duration(duration_str);
+    }
+    // Number and unit pairs separated by whitespace, like "1h 30m", are single-letter unit words
+    if duration_str.contains(char::is_whitespace)
+        && duration_str.contains(|c: char| c.is_ascii_alphabetic())
+        && !duration_str.contains(':')
+    {
+        return parse_word_duration(duration_str);
+    }
+    if duration_str.contains(char::is_whitespace) {
+        return Err(user_with_cause(
+            "Failed to parse the duration",
+            "Remove the whitespace from inside the duration (e.g., \"1:30\" instead of \"1 :30\")",
+            user(
+                "Unexpected whitespace",
+                "Make sure the duration is written without spaces between its parts",
+            ),
+        ));
+    }
+    if duration_str.contains(|c: char| c.is_ascii_alphabetic()) {
+        if duration_str.contains(':') {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                "Use either the \"[[[[w:]d:]h:]m:]s\" format or the unit suffix format (e.g., \"1h30m15s\"), not both",
+                user(
+                    "Mixed duration formats",
+                    "Remove either the colons or the unit letters from the duration",
+                ),
+            ));
+        }
+        return parse_suffix_duration(duration_str);
+    }
+
+    if duration_str.is_empty() {
+        return Err(user_with_cause(
+            "Failed to parse the duration",
+            "Provide the duration in the following format: \"[[[[w:]d:]h:]m:]s\"",
+            user(
+                "Missing parts",
+                "Make sure to provide at least the seconds part of the duration",
+            ),
+        ));
+    }
+    // Empty parts, like in "::5" or "1::", count as zero
+    let parts = duration_str.rsplit(':').take(6).collect::<Box<[_]>>();
+    if parts.iter().any(|part| part.starts_with('-')) {
+        return Err(negative_duration_error());
+    }
+    if parts.len() > 5 {
+        return Err(user_with_cause(
+            "Failed to parse the duration",
+            "Provide the duration in the following format: \"[[[[w:]d:]h:]m:]s\"",
+            user(
+                "Too many parts",
+                "Make sure to provide at most 5 parts for weeks, days, hours, minutes, and seconds",
+            ),
+        ));
+    }
+    let mut duration = Duration::ZERO;
+    for (part, (unit_secs, unit_name)) in parts.iter().copied().zip(COLON_UNITS) {
+        let (whole, fraction) = split_fraction(part)?;
+        let value = parse_part(whole).map_err(|err| {
+            if unit_secs == 1 {
+                user_with_internal(
+                    "Failed to parse the seconds part",
+                    "Make sure to provide a valid number for the seconds part",
+                    err,
+                )
+            } else {
+                user_with_internal(
+                    "Failed to parse a duration part",
+                    "Make sure to provide a valid number for the duration part",
+                    err,
+                )
+            }
+        })?;
+        let fraction = match fraction {
+            Some(fraction) => parse_fraction(fraction, unit_secs)?,
+            None => Duration::ZERO,
+        };
+        duration = value
+            .checked_mul(unit_secs)
+            .and_then(|secs| duration.checked_add(Duration::from_secs(secs)))
+            .and_then(|duration| duration.checked_add(fraction))
+            .ok_or_else(|| {
+                user_with_cause(
+                    "Duration overflow",
+                    "The provided duration is too large to be represented",
+                    user(
+                        &format!("Overflow in {unit_name}"),
+                        "Make sure the value is within a reasonable range",
+                    ),
+                )
+            })?;
+    }
+
+    Ok(duration)
+}
+
+/// The number of seconds in one unit of each part of the "[[[[w:]d:]h:]m:]s" format, from the
+/// seconds up.
+const COLON_UNITS: [(u64, &str); 5] = [
+    (1, "seconds"),
+    (60, "minutes"),
+    (3600, "hours"),
+    (86400, "days"),
+    (604800, "weeks"),
+];
+
+/// Splits a part of the "[[[[w:]d:]h:]m:]s" format into the whole number and the digits after
+/// the decimal separator, if any.
+fn split_fraction(part: &str) -> Result<(&str, Option<&str>), Error> {
+    if part.contains(',') && part.contains('.') {
+        return Err(user_with_cause(
+            "Failed to parse the duration",
+            "Use either a dot or a comma as the decimal separator, not both",
+            user(
+                "Ambiguous decimal separator",
+                "Make sure each part has at most one decimal separator",
+            ),
+        ));
+    }
+    // A comma is the decimal separator in many locales
+    let mut split = part.splitn(3, ['.', ',']);
+    match (split.next(), split.next(), split.next()) {
+        (Some(whole), fraction, None) => Ok((whole, fraction)),
+        _ => Err(user_with_cause(
+            "Failed to parse the duration",
+            "Provide the duration in the following format: \"[[[[w:]d:]h:]m:]s\"",
+            user(
+                "Too many decimal separators",
+                "Make sure to provide at most one decimal separator in each part",
+            ),
+        )),
+    }
+}
+
+/// Parses a part of the "[[[[w:]d:]h:]m:]s" format, where an empty part means zero.
+fn parse_part(part: &str) -> Result<u64, core::num::ParseIntError> {
+    if part.is_empty() { Ok(0) } else { part.parse() }
+}
+
+/// Parses a duration written as a sequence of number+unit pairs, like "1h30m15s" or "1.5h".
+///
+/// Units must appear at most once each and in descending order (`w`, `d`, `h`, `m`, `s`).
+/// The numbers may have a fraction, kept to millisecond precision.
+fn parse_suffix_duration(duration_str: &str) -> Result<Duration, Error> {
+    const ADVICE: &str = "Provide the duration as number and unit pairs in the following format: \"[Nw][Nd][Nh][Nm][Ns]\" (e.g., \"1h30m\" or \"1.5h\")";
+
+    let mut duration = Duration::ZERO;
+    let mut previous_unit_secs = None;
+    let mut rest = duration_str;
+    while !rest.is_empty() {
+        if rest.starts_with('-') {
+            return Err(negative_duration_error());
+        }
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let mut tail = tail.chars();
+        let Some(unit) = tail.next() else {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ADVICE,
+                user(
+                    "Missing unit",
+                    "Make sure every number is followed by one of the units: w, d, h, m, s",
+                ),
+            ));
+        };
+        rest = tail.as_str();
+
+        let (unit_secs, unit_name) = match unit {
+            'w' => (604800, "weeks"),
+            'd' => (86400, "days"),
+            'h' => (3600, "hours"),
+            'm' => (60, "minutes"),
+            's' => (1, "seconds"),
+            _ => {
+                return Err(user_with_cause(
+                    "Failed to parse the duration",
+                    ADVICE,
+                    user(
+                        &format!("Unknown unit '{unit}'"),
+                        "Make sure to only use the units: w, d, h, m, s",
+                    ),
+                ));
+            }
+        };
+        if number.is_empty() {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ADVICE,
+                user(
+                    &format!("Missing number before '{unit}'"),
+                    "Make sure every unit is preceded by a number",
+                ),
+            ));
+        }
+        if previous_unit_secs.is_some_and(|previous| previous <= unit_secs) {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ADVICE,
+                user(
+                    &format!("Unexpected unit '{unit}'"),
+                    "Make sure each unit appears at most once and units go from largest to smallest",
+                ),
+            ));
+        }
+        previous_unit_secs = Some(unit_secs);
+
+        duration = add_unit(duration, number, unit_secs, unit_name)?;
+    }
+
+    Ok(duration)
+}
+
+/// The unit words accepted after the numbers, with their lengths in seconds.
+const WORD_UNITS: [(&[&str], u64, &str); 5] = [
+    (&["weeks", "week", "wks", "wk", "w"], 604800, "weeks"),
+    (&["days", "day", "d"], 86400, "days"),
+    (&["hours", "hour", "hrs", "hr", "h"], 3600, "hours"),
+    (&["minutes", "minute", "mins", "min", "m"], 60, "minutes"),
+    (&["seconds", "second", "secs", "sec", "s"], 1, "seconds"),
+];
+
+/// Parses numbers followed by unit words, like "5 minutes", "1 hour 30 min" or "2days",
+/// ignoring the case of the words.
+fn parse_word_duration(duration_str: &str) -> Result<Duration, Error> {
+    const ADVICE: &str = "Provide the duration as numbers followed by units from largest to smallest (e.g., \"1 hour 30 minutes\")";
+
+    let mut duration = Duration::ZERO;
+    let mut previous_unit_secs = None;
+    let mut rest = duration_str;
+    while !rest.is_empty() {
+        if rest.starts_with('-') {
+            return Err(negative_duration_error());
+        }
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let tail = tail.trim_start();
+        let word_len = tail
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(tail.len());
+        let (word, tail) = tail.split_at(word_len);
+        rest = tail.trim_start();
+
+        if number.is_empty() {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ADVICE,
+                user(
+                    &format!("Missing number before {word:?}"),
+                    "Make sure every unit is preceded by a number",
+                ),
+            ));
+        }
+        if word.is_empty() {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ADVICE,
+                user(
+                    &format!("Missing unit after {number:?}"),
+                    "Make sure every number is followed by a unit",
+                ),
+            ));
+        }
+        let lowercase = word.to_ascii_lowercase();
+        let Some(&(_, unit_secs, unit_name)) = WORD_UNITS
+            .iter()
+            .find(|(words, ..)| words.contains(&lowercase.as_str()))
+        else {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ADVICE,
+                user(
+                    &format!("Unknown unit {word:?}"),
+                    "Make sure to only use the units: week(s)/wk(s), day(s), hour(s)/hr(s), minute(s)/min(s), second(s)/sec(s)",
+                ),
+            ));
+        };
+        if previous_unit_secs.is_some_and(|previous| previous <= unit_secs) {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ADVICE,
+                user(
+                    &format!("Unexpected unit {word:?}"),
+                    "Make sure each unit appears at most once and units go from largest to smallest",
+                ),
+            ));
+        }
+        previous_unit_secs = Some(unit_secs);
+        duration = add_unit(duration, number, unit_secs, unit_name)?;
+    }
+
+    Ok(duration)
+}
+
+/// Parses an ISO 8601 duration, like "PT1H30M15S" or "P1DT2H".
+///
+/// Years and months are rejected, because their length depends on the calendar.
+fn parse_iso8601_duration(duration_str: &str) -> Result<Duration, Error> {
+    let rest = duration_str.strip_prefix('P').unwrap_or(duration_str);
+    let (date_part, time_part) = match rest.split_once('T') {
+        Some((date_part, time_part)) => (date_part, Some(time_part)),
+        None => (rest, None),
+    };
+    if time_part == Some("") || (date_part.is_empty() && time_part.is_none()) {
+        return Err(user_with_cause(
+            "Failed to parse the duration",
+            ISO8601_ADVICE,
+            user(
+                "Missing components",
+                "Make sure to provide at least one component after 'P' and after 'T'",
+            ),
+        ));
+    }
+
+    let mut duration = parse_iso8601_components(date_part, &[('W', 604800), ('D', 86400)])?;
+    if let Some(time_part) = time_part {
+        duration = duration
+            .checked_add(parse_iso8601_components(
+                time_part,
+                &[('H', 3600), ('M', 60), ('S', 1)],
+            )?)
+            .ok_or_else(|| {
+                user_with_cause(
+                    "Duration overflow",
+                    "The provided duration is too large to be represented",
+                    user(
+                        "Overflow in the time components",
+                        "Make sure the value is within a reasonable range",
+                    ),
+                )
+            })?;
+    }
+
+    Ok(duration)
+}
+
+const ISO8601_ADVICE: &str = "Provide the duration in the ISO 8601 format: \"P[nW][nD][T[nH][nM][n[.f]S]]\" (e.g., \"PT1H30M15S\")";
+
+/// Parses the components of either the date or the time part of an ISO 8601 duration.
+///
+/// `designators` lists the allowed designators from largest to smallest,
+/// along with the number of seconds in one unit.
+/// Only the seconds component may have a fraction.
+fn parse_iso8601_components(
+    components: &str,
+    designators: &[(char, u64)],
+) -> Result<Duration, Error> {
+    let mut duration = Duration::ZERO;
+    let mut allowed = designators;
+    let mut rest = components;
+    while !rest.is_empty() {
+        if rest.starts_with('-') {
+            return Err(negative_duration_error());
+        }
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let mut tail = tail.chars();
+        let Some(designator) = tail.next() else {
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ISO8601_ADVICE,
+                user(
+                    "Missing designator",
+                    "Make sure every number is followed by a designator",
+                ),
+            ));
+        };
+        rest = tail.as_str();
+
+        let Some(position) = allowed.iter().position(|&(d, _)| d == designator) else {
+            let cause = if matches!(designator, 'Y' | 'M') && designators[0].0 == 'W' {
+                user(
+                    &format!("Unsupported designator '{designator}'"),
+                    "Years and months have no fixed length, express them in weeks or days instead",
+                )
+            } else {
+                user(
+                    &format!("Invalid designator '{designator}'"),
+                    "Make sure to use W and D before 'T', H, M and S after it, each at most once and in that order",
+                )
+            };
+            return Err(user_with_cause(
+                "Failed to parse the duration",
+                ISO8601_ADVICE,
+                cause,
+            ));
+        };
+        let unit_secs = allowed[position].1;
+        allowed = &allowed[position + 1..];
+
+        let (whole, fraction) = match number.split_once(['.', ',']) {
+            Some((whole, fraction)) if designator == 'S' => (whole, Some(fraction)),
+            Some(_) => {
+                return Err(user_with_cause(
+                    "Failed to parse the duration",
+                    ISO8601_ADVICE,
+                    user(
+                        &format!("Fractional value for '{designator}'"),
+                        "Make sure only the seconds component has a fraction",
+                    ),
+                ));
+            }
+            None => (number, None),
+        };
+        let value = whole.parse::<u64>().map_err(|err| {
+            user_with_internal(
+                "Failed to parse a duration part",
+                "Make sure to provide a valid number for the duration part",
+                err,
+            )
+        })?;
+        let fraction = match fraction {
+            Some(fraction) => parse_fraction(fraction, unit_secs)?,
+            None => Duration::ZERO,
+        };
+        duration = value
+            .checked_mul(unit_secs)
+            .and_then(|secs| duration.checked_add(Duration::from_secs(secs)))
+            .and_then(|duration| duration.checked_add(fraction))
+            .ok_or_else(|| {
+                user_with_cause(
+                    "Duration overflow",
+                    "The provided duration is too large to be represented",
+                    user(
+                        &format!("Overflow in '{designator}'"),
+                        "Make sure the value is within a reasonable range",
+                    ),
+                )
+            })?;
+    }
+
+    Ok(duration)
+}
+
+/// Adds `number` of a unit of `unit_secs` seconds to `duration`, where the number may have
+/// a fraction after a dot or a comma.
+fn add_unit(
+    duration: Duration,
+    number: &str,
+    unit_secs: u64,
+    unit_name: &str,
+) -> Result<Duration, Error> {
+    let (whole, fraction) = match number.split_once(['.', ',']) {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (number, None),
+    };
+    let value = whole.parse::<u64>().map_err(|err| {
+        user_with_internal(
+            "Failed to parse a duration part",
+            "Make sure to provide a valid number for the duration part",
+            err,
+        )
+    })?;
+    let fraction = match fraction {
+        Some(fraction) => parse_fraction(fraction, unit_secs)?,
+        None => Duration::ZERO,
+    };
+    value
+        .checked_mul(unit_secs)
+        .and_then(|secs| duration.checked_add(Duration::from_secs(secs)))
+        .and_then(|duration| duration.checked_add(fraction))
+        .ok_or_else(|| {
+            user_with_cause(
+                "Duration overflow",
+                "The provided duration is too large to be represented",
+                user(
+                    &format!("Overflow in {unit_name}"),
+                    "Make sure the value is within a reasonable range",
+                ),
+            )
+        })
+}
+
+fn negative_duration_error() -> Error {
+    user(
+        "Negative durations are not supported",
+        "Make sure every part of the duration is a non-negative number",
+    )
+}
+
+/// Parses the digits after a decimal separator as a fraction of a unit of `unit_secs` seconds,
+/// truncated to millisecond precision (e.g., "5" of a second is 500ms and "05" is 50ms).
+///
+/// The fraction is computed exactly, so "25" of an hour is 15 minutes on the dot.
+fn parse_fraction(fraction: &str, unit_secs: u64) -> Result<Duration, Error> {
+    if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(user(
+            "Failed to parse the fractional part",
+            "Make sure to provide only digits after the decimal separator",
+        ));
+    }
+    // Digits past the 18th are far below a millisecond even for weeks, and would overflow
+    let (numerator, denominator) =
+        fraction
+            .bytes()
+            .take(18)
+            .fold((0u128, 1u128), |(numerator, denominator), digit| {
+                (numerator * 10 + u128::from(digit - b'0'), denominator * 10)
+            });
+    let millis = numerator * u128::from(unit_secs) * 1000 / denominator;
+    // Less than one unit, which fits
+    Ok(Duration::from_millis(millis as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that parsing `input` fails with a user error mentioning `cause`.
+    #[track_caller]
+    fn assert_rejected(input: &str, cause: &str) {
+        let err = parse_duration(input).unwrap_err();
+        assert!(err.is_user(), "{input}");
+        assert!(err.message().contains(cause), "{input}: {}", err.message());
+    }
+
+    #[test]
+    fn parse_colon_format() {
+        assert_eq!(parse_duration("4").unwrap(), Duration::from_secs(4));
+        assert_eq!(parse_duration("3:4").unwrap(), Duration::from_secs(184));
+        assert_eq!(parse_duration("2:3:4").unwrap(), Duration::from_secs(7384));
+        assert_eq!(
+            parse_duration("1:2:3:4").unwrap(),
+            Duration::from_secs(93784)
+        );
+        assert_eq!(parse_duration("0").unwrap(), Duration::ZERO);
+        assert_eq!(parse_duration("90:00").unwrap(), Duration::from_secs(5400));
+        assert_eq!(
+            "1:30".parse::<ColonSeparatedDuration>().unwrap().0,
+            Duration::from_secs(90)
+        );
+    }
+
+    #[test]
+    fn parse_empty_parts() {
+        assert_eq!(parse_duration(":30").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("::5").unwrap(), Duration::from_secs(5));
+        assert_eq!(parse_duration("1:").unwrap(), Duration::from_secs(60));
+        assert_eq!(parse_duration("1::").unwrap(), Duration::from_secs(3600));
+        assert_eq!(parse_duration("1:::").unwrap(), Duration::from_secs(86400));
+        assert_eq!(
+            parse_duration("1::::").unwrap(),
+            Duration::from_secs(604800)
+        );
+        assert_eq!(parse_duration("1::30").unwrap(), Duration::from_secs(3630));
+        assert_eq!(parse_duration(":").unwrap(), Duration::ZERO);
+        assert_eq!(
+            parse_duration("1:.5").unwrap(),
+            Duration::from_millis(60500)
+        );
+        assert_rejected(":::::", "Too many parts");
+    }
+
+    #[test]
+    fn reject_overflow() {
+        for (unit, secs, slot) in [
+            ("minutes", 60, ""),
+            ("hours", 3600, ":0"),
+            ("days", 86400, ":0:0"),
+            ("weeks", 604800, ":0:0:0"),
+        ] {
+            let cause = format!("Overflow in {unit}");
+            // The part alone doesn't fit into the seconds
+            assert_rejected(&format!("{}{slot}:0", u64::MAX / secs + 1), &cause);
+            // The part fits, but not together with the seconds
+            assert_rejected(&format!("1{slot}:{}", u64::MAX), &cause);
+        }
+        assert_eq!(
+            parse_duration(&u64::MAX.to_string()).unwrap(),
+            Duration::from_secs(u64::MAX)
+        );
+        assert_rejected(
+            &format!("{}0", u64::MAX),
+            "Failed to parse the seconds part",
+        );
+    }
+
+    #[test]
+    fn reject_malformed_parts() {
+        assert_rejected("1:1:0:0:0:0", "Too many parts");
+        assert_rejected("1.2.3", "Too many decimal separators");
+        assert_rejected("1.2.3:0", "Too many decimal separators");
+        assert_rejected("1.", "Failed to parse the fractional part");
+        assert_rejected("1.+5", "Failed to parse the fractional part");
+        assert_rejected("1_0:30", "Failed to parse a duration part");
+        assert_rejected("", "Missing parts");
+        assert_rejected("  ", "Missing parts");
+    }
+
+    #[test]
+    fn parse_fractional_seconds() {
+        assert_eq!(
+            parse_duration("90.5").unwrap(),
+            Duration::from_secs(90) + Duration::from_millis(500)
+        );
+        assert_eq!(parse_duration("0.25").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("0.05").unwrap(), Duration::from_millis(50));
+        assert_eq!(
+            parse_duration("1:0.125").unwrap(),
+            Duration::from_millis(60125)
+        );
+        // Only milliseconds are kept
+        assert_eq!(
+            parse_duration("1.1239").unwrap(),
+            Duration::from_millis(1123)
+        );
+    }
+
+    #[test]
+    fn parse_fractional_parts() {
+        assert_eq!(parse_duration("1.5:0").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("1.5:30").unwrap(), Duration::from_secs(120));
+        assert_eq!(
+            parse_duration("0.25:0:0").unwrap(),
+            Duration::from_secs(900)
+        );
+        assert_eq!(
+            parse_duration("1.5:0:0:0").unwrap(),
+            Duration::from_secs(129_600)
+        );
+        assert_eq!(parse_duration(".5:0").unwrap(), Duration::from_secs(30));
+        // Only milliseconds are kept
+        assert_eq!(
+            parse_duration("0.0001:0").unwrap(),
+            Duration::from_millis(6)
+        );
+        assert_rejected("1.:0", "Failed to parse the fractional part");
+        assert_rejected(
+            &format!("{}.5:0:0:0:0", u64::MAX / 604800),
+            "Overflow in weeks",
+        );
+    }
+
+    #[test]
+    fn parse_decimal_comma() {
+        assert_eq!(parse_duration("1,5").unwrap(), Duration::from_millis(1500));
+        assert_eq!(
+            parse_duration("2:03,25").unwrap(),
+            Duration::from_millis(123_250)
+        );
+        assert_eq!(parse_duration("1,5:0").unwrap(), Duration::from_secs(90));
+        for input in ["1,5.0", "1.5,0", "1,5,0", "1,5.0:30"] {
+            assert!(parse_duration(input).unwrap_err().is_user(), "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_weeks() {
+        assert_eq!(
+            parse_duration("1:0:0:0:0").unwrap(),
+            Duration::from_secs(604800)
+        );
+        assert_eq!(
+            parse_duration("2w1d").unwrap(),
+            Duration::from_secs(1296000)
+        );
+        assert!(parse_duration("1:1:0:0:0:0").unwrap_err().is_user());
+    }
+
+    #[test]
+    fn reject_negative_parts() {
+        for input in [
+            "-5", "1:-5", "-1:5", "1:-2:3", "-1:2:3:4", "-5m", "1h-5m", "PT-5S", "-PT5S",
+        ] {
+            let err = parse_duration(input).unwrap_err();
+            assert_eq!(
+                err.description(),
+                "Negative durations are not supported",
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn trim_surrounding_whitespace() {
+        assert_eq!(parse_duration("  1:30  ").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("\t5m\n").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1h 30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(
+            parse_duration("1 hour 30m").unwrap(),
+            Duration::from_secs(5400)
+        );
+        for input in ["1 :30", "1: 30", "1h :30", "1 30m"] {
+            assert!(parse_duration(input).unwrap_err().is_user(), "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_suffix_format() {
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration("90m15s").unwrap(), Duration::from_secs(5415));
+        assert_eq!(
+            parse_duration("1d2h3m4s").unwrap(),
+            Duration::from_secs(93784)
+        );
+    }
+
+    #[test]
+    fn parse_fractional_units() {
+        for (input, millis) in [
+            ("1.5h", 5_400_000),
+            ("0.25h", 900_000),
+            ("2.5m", 150_000),
+            ("1,5d", 129_600_000),
+            ("1h0.5m", 3_630_000),
+            ("0.1s", 100),
+            ("0.0001s", 0),
+            ("0.333333333333333333333h", 1_199_999),
+        ] {
+            assert_eq!(
+                parse_duration(input).unwrap(),
+                Duration::from_millis(millis),
+                "{input}"
+            );
+        }
+        for input in ["1.h", ".5h", "1.5.5h", "1.5,5h", "1.-5h"] {
+            assert!(parse_duration(input).unwrap_err().is_user(), "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_single_unit() {
+        for (input, secs) in [
+            ("90s", 90),
+            ("5m", 300),
+            ("2h", 7200),
+            ("3d", 259200),
+            ("1w", 604800),
+            ("0s", 0),
+        ] {
+            assert_eq!(
+                parse_duration(input).unwrap(),
+                Duration::from_secs(secs),
+                "{input}"
+            );
+        }
+        for input in ["s", "5x", "m5", "5mm"] {
+            assert!(parse_duration(input).unwrap_err().is_user(), "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_word_format() {
+        for (input, secs) in [
+            ("5 minutes", 300),
+            ("1 hour 30 minutes", 5400),
+            ("2 days", 172800),
+            ("1 Week", 604800),
+            ("1hr 30min", 5400),
+            ("1 hour 30m", 5400),
+            ("2days", 172800),
+            ("1 min 1 sec", 61),
+            ("1.5 hours", 5400),
+        ] {
+            assert_eq!(
+                parse_duration(input).unwrap(),
+                Duration::from_secs(secs),
+                "{input}"
+            );
+        }
+        assert_rejected("5 fortnights", "Unknown unit \"fortnights\"");
+        assert_rejected("5 minutes 1 hour", "Unexpected unit \"hour\"");
+        assert_rejected("minutes", "Missing number");
+        assert_rejected("1 hour 30", "Missing unit");
+        assert_rejected("-5 minutes", "Negative");
+    }
+
+    #[test]
+    fn parse_iso8601_format() {
+        assert_eq!(
+            parse_duration("PT1H30M15S").unwrap(),
+            Duration::from_secs(5415)
+        );
+        assert_eq!(
+            parse_duration("P1DT2H").unwrap(),
+            Duration::from_secs(93600)
+        );
+        assert_eq!(parse_duration("P1W").unwrap(), Duration::from_secs(604800));
+        assert_eq!(
+            parse_duration("PT0.5S").unwrap(),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_iso8601_format() {
+        for input in ["P", "PT", "P1Y", "P1M", "PT1D", "PT1M1H", "PT1.5M", "P1DT"] {
+            assert!(parse_duration(input).unwrap_err().is_user(), "{input}");
+        }
+    }
+
+    #[test]
+    fn reject_invalid_suffix_format() {
+        for input in ["1:30m", "1h30", "h", "1x", "30m1h", "1m1m"] {
+            assert!(parse_duration(input).unwrap_err().is_user(), "{input}");
+        }
+    }
+}
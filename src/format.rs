@@ -0,0 +1,429 @@
+use core::{fmt, str::FromStr, time::Duration};
+
+use chrono::{Local, TimeDelta};
+use crossterm::style::Color;
+use human_errors::{Error, user, user_with_cause};
+
+use crate::{DurationStyle, remaining_ratio};
+
+const FORMAT_ADVICE: &str = "Use the tokens {remaining}, {elapsed}, {total}, {percent} and {end_time}, and write \"{{\" or \"}}\" for literal braces";
+
+/// A user-provided template for the running display, like "{remaining} / {total} ({percent}%)".
+#[derive(Debug, Clone)]
+pub struct DisplayFormat(Box<[Segment]>);
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Token(Token),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Remaining,
+    Elapsed,
+    Total,
+    Percent,
+    EndTime,
+}
+
+impl DisplayFormat {
+    /// Renders the template for a timer with `duration` left out of `initial_duration`.
+    ///
+    /// Tokens that need an end, like `{remaining}`, render as "-" when `has_end` is false.
+    /// Durations are shown in the given `style`, and the end time in the given `clock_format`.
+    pub fn render(
+        &self,
+        initial_duration: Duration,
+        duration: Duration,
+        has_end: bool,
+        style: DurationStyle,
+        clock_format: ClockFormat,
+    ) -> String {
+        use fmt::Write;
+
+        let mut rendered = String::new();
+        for segment in &self.0 {
+            let _ = match segment {
+                Segment::Literal(literal) => rendered.write_str(literal),
+                Segment::Token(Token::Elapsed) => write!(
+                    rendered,
+                    "{}",
+                    style.display(initial_duration.saturating_sub(duration))
+                ),
+                Segment::Token(_) if !has_end => rendered.write_str("-"),
+                Segment::Token(Token::Remaining) => {
+                    write!(rendered, "{}", style.remaining(duration))
+                }
+                Segment::Token(Token::Total) => {
+                    write!(rendered, "{}", style.display(initial_duration))
+                }
+                Segment::Token(Token::Percent) => write!(
+                    rendered,
+                    "{:.0}",
+                    100.0 * remaining_ratio(initial_duration, duration)
+                ),
+                Segment::Token(Token::EndTime) => {
+                    write!(rendered, "{}", EndTimeDisplay(duration, clock_format))
+                }
+            };
+        }
+        rendered
+    }
+}
+
+/// How wall-clock times are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClockFormat {
+    /// Like "2:32 PM".
+    TwelveHour,
+    /// Like "14:32".
+    #[default]
+    TwentyFourHour,
+}
+
+impl ClockFormat {
+    fn pattern(self) -> &'static str {
+        match self {
+            ClockFormat::TwelveHour => "%-I:%M %p",
+            ClockFormat::TwentyFourHour => "%H:%M",
+        }
+    }
+}
+
+impl FromStr for ClockFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "12" => Ok(Self::TwelveHour),
+            "24" => Ok(Self::TwentyFourHour),
+            _ => Err(user(
+                "Failed to parse the clock format",
+                "Provide either \"12\" or \"24\" for the 12-hour or 24-hour clock",
+            )),
+        }
+    }
+}
+
+/// The local wall-clock time at which a timer with `duration` left will end, like "14:32".
+#[derive(Debug, Clone, Copy)]
+pub struct EndTimeDisplay(pub Duration, pub ClockFormat);
+
+impl fmt::Display for EndTimeDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let end_time = TimeDelta::from_std(self.0)
+            .ok()
+            .and_then(|duration| Local::now().checked_add_signed(duration));
+        match end_time {
+            Some(end_time) => write!(f, "{}", end_time.format(self.1.pattern())),
+            None => f.write_str("-"),
+        }
+    }
+}
+
+/// When the progress bar turns from green to yellow and then red, as the percentages of the
+/// time that remain, like "50,20".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarColors {
+    yellow: u8,
+    red: u8,
+}
+
+impl BarColors {
+    /// The color of the bar with `remaining_ratio` of the time left.
+    pub fn color(self, remaining_ratio: f64) -> Color {
+        let percent = 100.0 * remaining_ratio;
+        if percent <= f64::from(self.red) {
+            Color::Red
+        } else if percent <= f64::from(self.yellow) {
+            Color::Yellow
+        } else {
+            Color::Green
+        }
+    }
+}
+
+impl FromStr for BarColors {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ADVICE: &str = "Provide the percentages of the time left at which the bar turns yellow and then red, separated by a comma (e.g., \"50,20\")";
+        let (yellow, red) = s
+            .split_once(',')
+            .ok_or_else(|| user("Failed to parse the bar colors", ADVICE))?;
+        let percent = |percent: &str| {
+            percent
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|&percent| percent <= 100)
+                .ok_or_else(|| {
+                    user_with_cause(
+                        "Failed to parse the bar colors",
+                        ADVICE,
+                        user(
+                            &format!("Invalid percentage {percent:?}"),
+                            "Use a whole number from 0 to 100",
+                        ),
+                    )
+                })
+        };
+        let (yellow, red) = (percent(yellow)?, percent(red)?);
+        if red > yellow {
+            return Err(user(
+                "The bar turns red before it turns yellow",
+                "Put the larger percentage, for yellow, first (e.g., \"50,20\")",
+            ));
+        }
+        Ok(Self { yellow, red })
+    }
+}
+
+/// The color of the running time, like "cyan", "#00ff80" or "rgb(0, 255, 128)".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextColor(pub Color);
+
+impl FromStr for TextColor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ADVICE: &str = "Provide a color name like \"cyan\" or \"dark_green\", a hex color like \"#00ff80\", or \"rgb(0, 255, 128)\"";
+        let s = s.trim();
+        let color = if let Some(hex) = s.strip_prefix('#') {
+            let channel = |index: usize| {
+                hex.get(index..index + 2)
+                    .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            };
+            match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb { r, g, b }),
+                _ => Err(user(
+                    &format!("Invalid hex color \"{s}\""),
+                    "Write the red, green and blue channels as two hex digits each",
+                )),
+            }
+        } else if let Some(channels) = s
+            .strip_prefix("rgb(")
+            .and_then(|channels| channels.strip_suffix(')'))
+        {
+            let channels: Vec<_> = channels
+                .split(',')
+                .map(|channel| channel.trim().parse::<u8>().ok())
+                .collect();
+            match channels[..] {
+                [Some(r), Some(g), Some(b)] => Ok(Color::Rgb { r, g, b }),
+                _ => Err(user(
+                    &format!("Invalid RGB color \"{s}\""),
+                    "Write the red, green and blue channels as whole numbers from 0 to 255",
+                )),
+            }
+        } else {
+            Color::try_from(s).map_err(|()| {
+                user(
+                    &format!("Unknown color \"{s}\""),
+                    "Use one of black, dark_grey, red, dark_red, green, dark_green, yellow, dark_yellow, blue, dark_blue, magenta, dark_magenta, cyan, dark_cyan, white or grey",
+                )
+            })
+        };
+        color
+            .map(Self)
+            .map_err(|err| user_with_cause("Failed to parse the color", ADVICE, err))
+    }
+}
+
+impl FromStr for DisplayFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let Some((name, tail)) = rest.split_once('}') else {
+                        return Err(user_with_cause(
+                            "Failed to parse the display format",
+                            FORMAT_ADVICE,
+                            user(
+                                "Unclosed '{' in the display format",
+                                "Make sure every token is closed with '}'",
+                            ),
+                        ));
+                    };
+                    let token = match name {
+                        "remaining" => Token::Remaining,
+                        "elapsed" => Token::Elapsed,
+                        "total" => Token::Total,
+                        "percent" => Token::Percent,
+                        "end_time" => Token::EndTime,
+                        _ => {
+                            return Err(user_with_cause(
+                                "Failed to parse the display format",
+                                FORMAT_ADVICE,
+                                user(
+                                    &format!("Unknown token {{{name}}}"),
+                                    "Make sure to only use the supported tokens",
+                                ),
+                            ));
+                        }
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(core::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Token(token));
+                    chars = tail.chars();
+                }
+                '}' => {
+                    return Err(user_with_cause(
+                        "Failed to parse the display format",
+                        FORMAT_ADVICE,
+                        user(
+                            "Unmatched '}' in the display format",
+                            "Make sure every '}' closes a token or is doubled as \"}}\"",
+                        ),
+                    ));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self(segments.into_boxed_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_tokens() {
+        let format = "{remaining} / {total} ({percent}%), {elapsed} in {{braces}}"
+            .parse::<DisplayFormat>()
+            .unwrap();
+        assert_eq!(
+            format.render(
+                Duration::from_secs(100),
+                Duration::from_secs(40),
+                true,
+                DurationStyle::default(),
+                ClockFormat::default(),
+            ),
+            "40s / 1m 40s (40%), 1m 0s in {braces}"
+        );
+        assert_eq!(
+            format.render(
+                Duration::from_secs(100),
+                Duration::from_millis(39_500),
+                true,
+                DurationStyle {
+                    millis: true,
+                    fixed_width: false,
+                    minutes_only: false,
+                },
+                ClockFormat::default(),
+            ),
+            "39.500s / 1m 40.000s (40%), 1m 0.500s in {braces}"
+        );
+        assert_eq!(
+            format.render(
+                Duration::MAX,
+                Duration::MAX - Duration::from_secs(5),
+                false,
+                DurationStyle::default(),
+                ClockFormat::default(),
+            ),
+            "- / - (-%), 5s in {braces}"
+        );
+    }
+
+    #[test]
+    fn render_end_time() {
+        let format = "{end_time}".parse::<DisplayFormat>().unwrap();
+        let render = |clock_format| {
+            format.render(
+                Duration::from_secs(60),
+                Duration::from_secs(60),
+                true,
+                DurationStyle::default(),
+                clock_format,
+            )
+        };
+        let twelve_hour = render(ClockFormat::TwelveHour);
+        assert!(
+            twelve_hour.ends_with(" AM") || twelve_hour.ends_with(" PM"),
+            "{twelve_hour}"
+        );
+        let twenty_four_hour = render(ClockFormat::TwentyFourHour);
+        assert_eq!(twenty_four_hour.len(), "14:32".len(), "{twenty_four_hour}");
+        assert!(!twenty_four_hour.contains('M'), "{twenty_four_hour}");
+    }
+
+    #[test]
+    fn pick_bar_color() {
+        let colors = "50,20".parse::<BarColors>().unwrap();
+        assert_eq!(colors.color(1.0), Color::Green);
+        assert_eq!(colors.color(0.5), Color::Yellow);
+        assert_eq!(colors.color(0.3), Color::Yellow);
+        assert_eq!(colors.color(0.2), Color::Red);
+        assert_eq!(colors.color(0.0), Color::Red);
+        for input in ["50", "20,50", "150,20", "a,b"] {
+            assert!(input.parse::<BarColors>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_text_color() {
+        let color = |s: &str| s.parse::<TextColor>().map(|TextColor(color)| color);
+        assert_eq!(color("cyan").unwrap(), Color::Cyan);
+        assert_eq!(color("Dark_Green").unwrap(), Color::DarkGreen);
+        assert_eq!(
+            color("#00ff80").unwrap(),
+            Color::Rgb {
+                r: 0,
+                g: 255,
+                b: 128
+            }
+        );
+        assert_eq!(
+            color("rgb(0, 255, 128)").unwrap(),
+            Color::Rgb {
+                r: 0,
+                g: 255,
+                b: 128
+            }
+        );
+        for input in [
+            "teal",
+            "#00ff8",
+            "#00ff8g",
+            "#ü0ff80",
+            "rgb(0, 256, 0)",
+            "rgb(0, 0)",
+        ] {
+            assert!(color(input).unwrap_err().is_user(), "{input}");
+        }
+    }
+
+    #[test]
+    fn reject_invalid_format() {
+        for input in ["{remainder}", "{remaining", "remaining}", "{}"] {
+            assert!(
+                input.parse::<DisplayFormat>().unwrap_err().is_user(),
+                "{input}"
+            );
+        }
+    }
+}
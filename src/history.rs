@@ -0,0 +1,229 @@
+use core::{fmt, time::Duration};
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Datelike, Local};
+use human_errors::{Error, system, user_with_internal};
+use serde::{Deserialize, Serialize};
+
+use crate::{DurationDisplay, status::millis};
+
+/// A finished or stopped timer, appended as a line of JSON to the history log.
+#[derive(Debug, Serialize)]
+pub struct HistoryEntry<'a> {
+    started_at: String,
+    /// `None` for a stopwatch without a cap.
+    duration_ms: Option<u64>,
+    elapsed_ms: u64,
+    label: Option<&'a str>,
+    outcome: HistoryOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOutcome {
+    Completed,
+    Stopped,
+}
+
+impl<'a> HistoryEntry<'a> {
+    pub fn new(
+        started_at: DateTime<Local>,
+        duration: Option<Duration>,
+        elapsed: Duration,
+        label: Option<&'a str>,
+        outcome: HistoryOutcome,
+    ) -> Self {
+        Self {
+            started_at: started_at.to_rfc3339(),
+            duration_ms: duration.map(millis),
+            elapsed_ms: millis(elapsed),
+            label,
+            outcome,
+        }
+    }
+
+    /// Appends the entry to `history.jsonl` in the user's data directory,
+    /// like `~/.local/share/timer-cli/history.jsonl`.
+    pub fn append(&self) -> Result<(), Error> {
+        let path = history_path()?;
+        self.append_to(&path).map_err(|err| {
+            user_with_internal(
+                &format!("Failed to write the history log {}", path.display()),
+                "Make sure the directory is writable",
+                err,
+            )
+        })
+    }
+
+    fn append_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        // A single write keeps lines from concurrent timers from interleaving
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(&line)
+    }
+}
+
+/// The part of a [`HistoryEntry`] that the statistics are made of.
+#[derive(Debug, Deserialize)]
+struct HistoryRecord {
+    started_at: String,
+    elapsed_ms: u64,
+    outcome: HistoryOutcome,
+}
+
+/// Totals over the history log, printed by `timer stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryStats {
+    /// The time spent on the timers started today.
+    today: Duration,
+    /// The time spent on the timers started this week, from Monday on.
+    this_week: Duration,
+    completed: u32,
+    stopped: u32,
+    /// The time spent on a timer, on average.
+    average: Duration,
+}
+
+impl HistoryStats {
+    /// Reads the statistics from the history log, which are all zero if there's no log yet.
+    pub fn read() -> Result<Self, Error> {
+        let path = history_path()?;
+        match fs::read_to_string(&path) {
+            Ok(contents) => Ok(Self::from_lines(&contents, Local::now())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(user_with_internal(
+                &format!("Failed to read the history log {}", path.display()),
+                "Make sure the file is readable",
+                err,
+            )),
+        }
+    }
+
+    /// Adds up the lines of the history log as of `now`.
+    ///
+    /// Lines that can't be parsed, like one cut short by a crash, are skipped rather than
+    /// making the whole log unusable.
+    fn from_lines(contents: &str, now: DateTime<Local>) -> Self {
+        let mut stats = Self::default();
+        let mut total = Duration::ZERO;
+        let records = contents.lines().filter_map(|line| {
+            let record = serde_json::from_str::<HistoryRecord>(line).ok()?;
+            let started_at = DateTime::parse_from_rfc3339(&record.started_at).ok()?;
+            Some((record, started_at.with_timezone(&Local)))
+        });
+        for (record, started_at) in records {
+            let elapsed = Duration::from_millis(record.elapsed_ms);
+            if started_at.date_naive() == now.date_naive() {
+                stats.today += elapsed;
+            }
+            if started_at.iso_week() == now.iso_week() {
+                stats.this_week += elapsed;
+            }
+            match record.outcome {
+                HistoryOutcome::Completed => stats.completed += 1,
+                HistoryOutcome::Stopped => stats.stopped += 1,
+            }
+            total += elapsed;
+        }
+        let count = stats.completed + stats.stopped;
+        if count > 0 {
+            stats.average = total / count;
+        }
+        stats
+    }
+}
+
+impl fmt::Display for HistoryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Today:     {}", DurationDisplay(self.today))?;
+        writeln!(f, "This week: {}", DurationDisplay(self.this_week))?;
+        writeln!(f, "Completed: {}", self.completed)?;
+        writeln!(f, "Stopped:   {}", self.stopped)?;
+        writeln!(f, "Average:   {}", DurationDisplay(self.average))
+    }
+}
+
+fn history_path() -> Result<PathBuf, Error> {
+    directories::ProjectDirs::from("", "", "timer-cli")
+        .map(|dirs| dirs.data_dir().join("history.jsonl"))
+        .ok_or_else(|| {
+            system(
+                "Failed to find the data directory for the history log",
+                "Make sure the HOME environment variable is set",
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    #[test]
+    fn append_lines() {
+        let dir = std::env::temp_dir().join(format!("timer-history-{}", std::process::id()));
+        let path = dir.join("nested").join("history.jsonl");
+        let started_at = DateTime::parse_from_rfc3339("2025-01-02T03:04:05+00:00")
+            .unwrap()
+            .with_timezone(&Local);
+        for outcome in [HistoryOutcome::Completed, HistoryOutcome::Stopped] {
+            HistoryEntry::new(
+                started_at,
+                Some(Duration::from_secs(60)),
+                Duration::from_millis(1500),
+                Some("Tea"),
+                outcome,
+            )
+            .append_to(&path)
+            .unwrap();
+        }
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(
+            "\"duration_ms\":60000,\"elapsed_ms\":1500,\"label\":\"Tea\",\"outcome\":\"completed\"}"
+        ));
+        assert!(lines[1].ends_with("\"outcome\":\"stopped\"}"));
+    }
+
+    #[test]
+    fn add_up_stats() {
+        let now = DateTime::parse_from_rfc3339("2025-01-08T12:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Local);
+        let line = |started_at: DateTime<Local>, elapsed_ms, outcome| {
+            format!(
+                "{{\"started_at\":\"{}\",\"duration_ms\":null,\"elapsed_ms\":{elapsed_ms},\"label\":null,\"outcome\":\"{outcome}\"}}\n",
+                started_at.to_rfc3339()
+            )
+        };
+        let contents = [
+            line(now, 1_500_000, "completed"),
+            line(now - TimeDelta::days(1), 600_000, "stopped"),
+            line(now - TimeDelta::weeks(1), 300_000, "completed"),
+            "{\"started_at\":".to_owned(),
+        ]
+        .concat();
+
+        let stats = HistoryStats::from_lines(&contents, now);
+        assert_eq!(stats.today, Duration::from_secs(1500));
+        assert_eq!(stats.this_week, Duration::from_secs(2100));
+        assert_eq!((stats.completed, stats.stopped), (2, 1));
+        assert_eq!(stats.average, Duration::from_secs(800));
+        assert_eq!(HistoryStats::from_lines("", now), HistoryStats::default());
+    }
+}
@@ -0,0 +1,90 @@
+use core::{fmt, time::Duration};
+
+use crate::DurationDisplay;
+
+/// The laps recorded on a stopwatch, as the elapsed time at each of them.
+#[derive(Debug, Clone, Default)]
+pub struct Laps(Vec<Duration>);
+
+/// A recorded lap, like "Lap 2: 1m 10s (+38s)".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lap {
+    /// The 1-based number of the lap.
+    number: usize,
+    /// The elapsed time at the end of the lap.
+    time: Duration,
+    /// How long the lap itself took.
+    split: Duration,
+}
+
+impl Laps {
+    /// Records a lap ending at `elapsed`, returning it.
+    pub fn record(&mut self, elapsed: Duration) -> Lap {
+        self.0.push(elapsed);
+        self.lap(self.0.len() - 1)
+    }
+
+    pub fn last(&self) -> Option<Lap> {
+        self.0.len().checked_sub(1).map(|index| self.lap(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn lap(&self, index: usize) -> Lap {
+        let time = self.0[index];
+        let previous = index
+            .checked_sub(1)
+            .map_or(Duration::ZERO, |index| self.0[index]);
+        Lap {
+            number: index + 1,
+            time,
+            split: time.saturating_sub(previous),
+        }
+    }
+}
+
+impl fmt::Display for Lap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Lap {}: {} (+{})",
+            self.number,
+            DurationDisplay(self.time),
+            DurationDisplay(self.split)
+        )
+    }
+}
+
+/// Lists every lap on its own line.
+impl fmt::Display for Laps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for index in 0..self.0.len() {
+            writeln!(f, "{}", self.lap(index))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_laps() {
+        let mut laps = Laps::default();
+        assert_eq!(laps.last(), None);
+        laps.record(Duration::from_secs(32));
+        let lap = laps.record(Duration::from_secs(70));
+        assert_eq!(laps.last(), Some(lap));
+        assert_eq!(lap.to_string(), "Lap 2: 1m 10s (+38s)");
+        assert_eq!(
+            laps.to_string(),
+            "Lap 1: 32s (+32s)\n\
+             Lap 2: 1m 10s (+38s)\n"
+        );
+        laps.clear();
+        assert_eq!(laps.last(), None);
+    }
+}
@@ -0,0 +1,5 @@
+//! Parsing of the durations accepted by the timer, for reuse outside of the command line.
+
+mod duration;
+
+pub use duration::{ColonSeparatedDuration, parse_duration};
@@ -1,243 +1,3912 @@
 use core::{fmt, str::FromStr, time::Duration};
-use std::{io, process::ExitCode};
+use std::{
+    io,
+    process::ExitCode,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeDelta};
+use clap::Parser;
+use crossterm::{
+    cursor,
+    event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style, terminal,
+    tty::IsTty,
+};
+use futures_util::{FutureExt, TryStreamExt};
+use human_errors::{Error, system_with_internal, user, user_with_cause, user_with_internal};
+use timer::{ColonSeparatedDuration, parse_duration};
+
+use crate::{
+    clock::Clock,
+    format::{BarColors, ClockFormat, DisplayFormat, EndTimeDisplay, TextColor},
+    history::{HistoryEntry, HistoryOutcome, HistoryStats},
+    laps::Laps,
+    output::{Output, OutputStream},
+    saved_timer::SavedTimer,
+    status::StatusEvent,
+    status_file::StatusFile,
+    stdin_commands::StdinCommands,
+    summary::Summary,
+    taskbar_progress::TaskbarProgress,
+    terminal_guard::TerminalGuard,
+    terminate_signal::TerminateSignal,
+};
+
+mod clock;
+mod config;
+mod format;
+mod history;
+mod laps;
+mod output;
+mod routine;
+mod saved_timer;
+#[cfg(feature = "sound")]
+mod sound;
+mod status;
+mod status_file;
+#[cfg(unix)]
+mod status_socket;
+mod stdin_commands;
+mod summary;
+mod taskbar_progress;
+mod terminal_guard;
+mod terminate_signal;
+
+fn main() -> ExitCode {
+    let Args {
+        durations,
+        from_file,
+        then,
+        stdin,
+        count_up,
+        repeat,
+        every,
+        until,
+        until_datetime,
+        random,
+        max,
+        warn_long,
+        warn_at: ColonSeparatedDuration(warn_at),
+        blink,
+        no_spinner,
+        format,
+        clock_format,
+        fps,
+        speed,
+        millis,
+        fixed_width,
+        coarse,
+        bar_colors,
+        color,
+        pause_key,
+        quit_key,
+        reset_key,
+        restart_key,
+        bell,
+        bell_count,
+        flash,
+        flash_count,
+        flash_interval: ColonSeparatedDuration(flash_interval),
+        announce,
+        #[cfg(feature = "notify")]
+        notify,
+        exec,
+        exec_on_stop,
+        #[cfg(feature = "sound")]
+        sound,
+        #[cfg(feature = "sound")]
+        sound_loop,
+        json,
+        quiet,
+        status_file,
+        #[cfg(unix)]
+        socket,
+        set_title,
+        taskbar_progress,
+        label,
+        save_state,
+        resume,
+        log,
+        dry_run,
+        examples,
+        version_raw,
+        wait_on_finish,
+        snooze,
+        no_message,
+        clear_on_exit,
+        pause_on_start,
+        delay,
+        gap,
+        inline,
+        no_raw_mode,
+        output,
+        stdin_commands,
+        accessible,
+        command,
+    } = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if version_raw {
+        println!("{}", env!("CARGO_PKG_VERSION"));
+        return ExitCode::SUCCESS;
+    }
+    if let Some(Command::Stats) = command {
+        return match HistoryStats::read() {
+            Ok(stats) => {
+                print!("{stats}");
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+    if examples {
+        print_examples();
+        return ExitCode::SUCCESS;
+    }
+
+    let mode = if count_up {
+        Mode::CountUp
+    } else {
+        Mode::CountDown
+    };
+    let steps = match (&resume, from_file) {
+        (Some(path), _) => SavedTimer::load(path).map(|saved| {
+            vec![Step {
+                duration: Some(saved.remaining()),
+                label: label.clone().or(saved.label),
+            }]
+        }),
+        (None, Some(path)) => routine::read(&path),
+        (None, None) => {
+            // The random duration is picked once, up front, so that --dry-run shows the real one
+            let durations = match (random, every) {
+                _ if stdin => routine::read_stdin()
+                    .map(|durations| durations.into_iter().map(ColonSeparatedDuration).collect()),
+                (Some(range), _) => Ok(vec![ColonSeparatedDuration(range.sample())]),
+                (None, Some(every)) => Ok(vec![every]),
+                (None, None) => Ok(durations),
+            };
+            durations.and_then(|durations| {
+                resolve_durations(
+                    durations,
+                    until
+                        .map(Until::TimeOfDay)
+                        .or(until_datetime.map(Until::DateTime)),
+                    mode,
+                )
+            })
+        }
+        .map(|durations| {
+            durations
+                .into_iter()
+                .map(|duration| Step {
+                    label: label.clone(),
+                    duration,
+                })
+                .collect()
+        }),
+    }
+    .map(|mut steps: Vec<Step>| {
+        steps.extend(then.into_iter().map(|ChainedTimer(step)| step));
+        steps
+    });
+    let steps = match steps
+        .and_then(|steps| reject_zero_durations(steps, mode))
+        .and_then(|steps| reject_long_durations(steps, max.map(|ColonSeparatedDuration(max)| max)))
+    {
+        Ok(steps) => steps,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let line_interval = match accessible {
+        Some(ColonSeparatedDuration(interval)) if interval.is_zero() => {
+            eprintln!(
+                "{}",
+                user(
+                    "The interval of the accessible mode is zero",
+                    "Provide a non-zero interval, like --accessible=30",
+                )
+            );
+            return ExitCode::FAILURE;
+        }
+        Some(ColonSeparatedDuration(interval)) => interval,
+        None => Duration::from_secs(1),
+    };
+    // Only the remaining time of a single timer is saved, which would lose the rest of the run
+    if (save_state.is_some() || resume.is_some())
+        && (steps.len() > 1 || every.is_some() || !repeat.is_last(1))
+    {
+        eprintln!(
+            "{}",
+            user(
+                "Only a single timer can be saved with --save-state or --resume",
+                "Run a single duration without --repeat, --every, --then or --from-file",
+            )
+        );
+        return ExitCode::FAILURE;
+    }
+    if let Some(ColonSeparatedDuration(Duration::ZERO)) = snooze {
+        eprintln!(
+            "{}",
+            user(
+                "The snooze duration is zero",
+                "Provide a positive duration, like --snooze 5:00",
+            )
+        );
+        return ExitCode::FAILURE;
+    }
+    let keys = match KeyBindings::new(pause_key, quit_key, reset_key, restart_key) {
+        Ok(keys) => keys,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if dry_run {
+        print_steps(&steps);
+        return ExitCode::SUCCESS;
+    }
+
+    let rt = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| {
+            system_with_internal(
+                "Failed to build the runtime",
+                "Try notifying the developer",
+                err,
+            )
+        }) {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Without a terminal to answer in, e.g., in a script, the timer starts without asking
+    if let Some(ColonSeparatedDuration(threshold)) = warn_long
+        && let Some(duration) = find_longer_duration(&steps, threshold)
+        && std::io::stdin().is_tty()
+        && output.writer().is_tty()
+    {
+        match rt.block_on(async {
+            let mut terminate = TerminateSignal::new().map_err(|err| {
+                system_with_internal(
+                    "Failed to listen for SIGTERM",
+                    "Try notifying the developer",
+                    err,
+                )
+            })?;
+            confirm_long_duration(&mut output.writer(), duration, threshold, &mut terminate).await
+        }) {
+            Ok(true) => {}
+            Ok(false) => {
+                if !no_message {
+                    eprintln!(
+                        "{} cancelled before it started.",
+                        Named::timer(&steps[0].label)
+                    );
+                }
+                return ExitCode::from(INTERRUPTED_EXIT_CODE);
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    let socket = match socket.map(|path| {
+        status_socket::StatusSocket::bind(path.clone()).map_err(|err| {
+            user_with_internal(
+                &format!("Failed to create the socket {}", path.display()),
+                "Make sure the directory exists and no other timer is using the socket",
+                err,
+            )
+        })
+    }) {
+        Some(Ok(socket)) => Some(socket),
+        Some(Err(e)) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+        None => None,
+    };
+
+    let options = TimerOptions {
+        mode,
+        repeat: if every.is_some() {
+            Repeat::Infinite
+        } else {
+            repeat
+        },
+        every: every.is_some(),
+        warn_at,
+        blink,
+        spinner: !no_spinner,
+        hidden: random.is_some(),
+        format,
+        clock_format,
+        tick_period: Duration::from_secs(1) / fps,
+        clock: Clock::new(speed),
+        duration_style: DurationStyle {
+            millis,
+            fixed_width,
+            minutes_only: false,
+        },
+        coarse: coarse.map(|ColonSeparatedDuration(coarse)| coarse),
+        bar_colors,
+        color: color.map(|TextColor(color)| color),
+        keys,
+        bell_count: if bell { bell_count } else { 0 },
+        flash_count: if flash { flash_count } else { 0 },
+        flash_interval,
+        announce: announce.into_iter().map(Duration::from_secs).collect(),
+        #[cfg(feature = "notify")]
+        notify,
+        exec,
+        exec_on_stop,
+        #[cfg(feature = "sound")]
+        sound,
+        #[cfg(feature = "sound")]
+        sound_loop,
+        status_file,
+        #[cfg(unix)]
+        socket,
+        set_title,
+        taskbar_progress,
+        label,
+        // A resumed timer is saved back to where it came from
+        save_state: save_state.or(resume),
+        log,
+        wait_on_finish,
+        snooze: snooze.map(|ColonSeparatedDuration(snooze)| snooze),
+        no_message,
+        clear_on_exit,
+        pause_on_start,
+        delay: delay.map(|ColonSeparatedDuration(delay)| delay),
+        gap: gap
+            .map(|ColonSeparatedDuration(gap)| gap)
+            .filter(|gap| !gap.is_zero()),
+        inline,
+        no_raw_mode,
+        output,
+        // The accessible mode is controlled with typed commands
+        stdin_commands: stdin_commands || accessible.is_some(),
+        accessible: accessible.is_some(),
+        line_interval,
+    };
+    let interface = if quiet {
+        Interface::Quiet
+    } else if json {
+        Interface::Json
+    } else {
+        Interface::Terminal
+    };
+    match rt.block_on(run_sequence(&steps, &options, interface)) {
+        Ok(Outcome::Interrupted(elapsed)) => {
+            if options.exec_on_stop
+                && let Err(e) = run_exec(&options, elapsed, "stopped")
+            {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+            ExitCode::from(INTERRUPTED_EXIT_CODE)
+        }
+        Ok(Outcome::Completed(_) | Outcome::Reset) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parses the command-line arguments on top of the defaults from the config file.
+fn parse_args() -> Result<Args, Error> {
+    use clap::CommandFactory;
+
+    let config_args = config::load_args(&Args::command())?;
+    let mut args = std::env::args_os();
+    let mut args = Args::parse_from(args.next().into_iter().chain(config_args).chain(args));
+    // `timer resume PATH` is the same as `timer --resume PATH`, with the same conflicts
+    match args.command.take() {
+        Some(Command::Resume { path }) => {
+            if args.resume.is_some()
+                || args.from_file.is_some()
+                || args.until.is_some()
+                || args.until_datetime.is_some()
+                || args.count_up
+                || args.stdin
+                || args.every.is_some()
+                || args.random.is_some()
+            {
+                Args::command()
+                    .error(
+                        clap::error::ErrorKind::ArgumentConflict,
+                        "the resume command cannot be used with --resume, --from-file, --until, --until-datetime, --count-up, --stdin, --every or --random",
+                    )
+                    .exit();
+            }
+            args.resume = Some(path);
+        }
+        command => args.command = command,
+    }
+    Ok(args)
+}
+
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_override_self = true,
+    // Durations and commands can't be combined, which the generated usage doesn't show
+    override_usage = "timer [OPTIONS] [[[[[w:]d:]h:]m:]s duration]...\n       timer [OPTIONS] <COMMAND>"
+)]
+struct Args {
+    #[arg(
+        name = "[[[[w:]d:]h:]m:]s duration",
+        allow_negative_numbers = true,
+        help = "Duration in the format \"[[[[w:]d:]h:]m:]s\" (e.g., \"1:2:3:4\" for 1 day, 2 hours, 3 minutes, and 4 seconds, or \"1:0:0:0:0\" for 1 week), with unit suffixes (e.g., \"1h30m15s\" or \"1.5h\"), with unit words quoted as one argument (e.g., \"5 minutes\" or \"1 hour 30 min\"), or in ISO 8601 (e.g., \"PT1H30M15S\"). Falls back to the TIMER_DURATION environment variable when omitted. Several durations run one after another"
+    )]
+    durations: Vec<ColonSeparatedDuration>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["[[[[w:]d:]h:]m:]s duration", "until", "until_datetime"],
+        help = "Run the timers listed in a file one after another, one \"<label> <duration>\" per line, skipping blank lines and # comments"
+    )]
+    from_file: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        value_name = "[LABEL=]DURATION",
+        conflicts_with_all = ["every", "random"],
+        help = "Start another timer once the previous one finishes, named with an optional label (e.g., \"Break=5:00\"), in the same order when given several times"
+    )]
+    then: Vec<ChainedTimer>,
+    #[arg(
+        long,
+        conflicts_with_all = ["[[[[w:]d:]h:]m:]s duration", "from_file", "until", "until_datetime", "random", "every", "resume", "stdin_commands", "accessible"],
+        help = "Read the durations to run one after another from stdin, one per line, skipping blank lines and # comments"
+    )]
+    stdin: bool,
+    #[arg(
+        long,
+        help = "Count up from zero like a stopwatch, stopping at the duration if one is given"
+    )]
+    count_up: bool,
+    #[arg(
+        long,
+        value_name = "N|infinite",
+        default_value = "1",
+        help = "Run the timer N times in a row, or until stopped with \"infinite\""
+    )]
+    repeat: Repeat,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        conflicts_with_all = ["[[[[w:]d:]h:]m:]s duration", "until", "until_datetime", "random", "from_file", "resume", "count_up", "repeat"],
+        help = "Count down the interval over and over until stopped, ringing the bell, sending the notification and running the command after each one (e.g., \"20:00\" for a reminder every 20 minutes)"
+    )]
+    every: Option<ColonSeparatedDuration>,
+    #[arg(
+        long,
+        value_name = "HH:MM[:SS] [AM|PM]",
+        help = "Count down to the next occurrence of the given local time instead of a duration, on either clock (e.g., \"17:00\" or \"5:00 PM\")"
+    )]
+    until: Option<TimeOfDay>,
+    #[arg(
+        long,
+        value_name = "DATETIME",
+        conflicts_with_all = ["[[[[w:]d:]h:]m:]s duration", "until"],
+        help = "Count down to the given local date and time as \"YYYY-MM-DD HH:MM[:SS]\" instead of a duration, for targets beyond today (e.g., \"2026-12-31 23:59\")"
+    )]
+    until_datetime: Option<LocalDateTime>,
+    #[arg(
+        long,
+        value_name = "MIN..MAX",
+        conflicts_with_all = ["[[[[w:]d:]h:]m:]s duration", "until", "until_datetime", "from_file", "resume", "count_up", "format", "announce", "json"],
+        help = "Count down a random duration between MIN and MAX (e.g., \"1:00..5:00\"), keeping it hidden until the timer finishes"
+    )]
+    random: Option<DurationRange>,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        help = "Refuse to start a timer longer than this, e.g., to catch a typo like \"1000:0:0\" in a script"
+    )]
+    max: Option<ColonSeparatedDuration>,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        help = "Ask before starting a timer longer than this when run interactively, e.g., to catch a typo like \"100:0:0\" that would run for days"
+    )]
+    warn_long: Option<ColonSeparatedDuration>,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "10",
+        help = "Turn the time red when this much time or less remains (\"0\" to disable)"
+    )]
+    warn_at: ColonSeparatedDuration,
+    #[arg(
+        long,
+        help = "Blink the time once it turns red at --warn-at, best combined with a higher --fps"
+    )]
+    blink: bool,
+    #[arg(
+        long,
+        help = "Leave out the spinner that turns next to the time on every tick"
+    )]
+    no_spinner: bool,
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        help = "Template for the running display, using the tokens {remaining}, {elapsed}, {total}, {percent} and {end_time} (e.g., \"{remaining} / {total} ({percent}%)\")"
+    )]
+    format: Option<DisplayFormat>,
+    #[arg(
+        long,
+        value_name = "12|24",
+        default_value = "24",
+        help = "Show wall-clock times, like the end time, on the 12-hour (\"2:32 PM\") or 24-hour (\"14:32\") clock"
+    )]
+    clock_format: ClockFormat,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..=60),
+        help = "How many times per second to redraw the display, for a smoother progress bar"
+    )]
+    fps: u32,
+    #[arg(
+        long,
+        value_name = "FACTOR",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        hide = true,
+        help = "Run the clock this many times faster than real time, to test long timers quickly"
+    )]
+    speed: u32,
+    #[arg(
+        long,
+        help = "Show the milliseconds of the running time, best combined with a higher --fps"
+    )]
+    millis: bool,
+    #[arg(
+        long,
+        help = "Show the running time with fixed-width fields, like \"01:05:09\", so that it doesn't jump around"
+    )]
+    fixed_width: bool,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10:00",
+        help = "Show only the minutes while more than DURATION (10 minutes if omitted) remains, redrawing once a minute"
+    )]
+    coarse: Option<ColonSeparatedDuration>,
+    #[arg(
+        long,
+        value_name = "YELLOW,RED",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "50,20",
+        help = "Color the progress bar green, turning yellow once YELLOW percent of the time is left and red once RED percent is (50,20 if omitted)"
+    )]
+    bar_colors: Option<BarColors>,
+    #[arg(
+        long,
+        value_name = "NAME|#RRGGBB",
+        help = "Color the running time with a named color (e.g., \"cyan\" or \"dark_green\"), \"#RRGGBB\" or \"rgb(R, G, B)\", until it turns red at --warn-at"
+    )]
+    color: Option<TextColor>,
+    #[arg(
+        long,
+        value_name = "KEY",
+        default_value_t = 'p',
+        help = "Key that pauses and resumes the timer"
+    )]
+    pause_key: char,
+    #[arg(
+        long,
+        value_name = "KEY",
+        default_value_t = 'q',
+        help = "Key that stops the timer (Ctrl+C always does)"
+    )]
+    quit_key: char,
+    #[arg(
+        long,
+        value_name = "KEY",
+        default_value_t = 'r',
+        help = "Key that restarts the timer from the beginning, or the whole sequence of several durations"
+    )]
+    reset_key: char,
+    #[arg(
+        long,
+        value_name = "KEY",
+        default_value_t = 'R',
+        help = "Key that restarts only the current one of several durations"
+    )]
+    restart_key: char,
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        default_value_t = true,
+        default_missing_value = "true",
+        action = clap::ArgAction::Set,
+        help = "Ring the terminal bell when the timer finishes"
+    )]
+    bell: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        help = "How many times to ring the bell when the timer finishes"
+    )]
+    bell_count: u32,
+    #[arg(long, help = "Flash the screen when the timer finishes")]
+    flash: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 3,
+        help = "How many times to flash the screen with --flash"
+    )]
+    flash_count: u32,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "0.25",
+        help = "How long each flash of --flash lasts"
+    )]
+    flash_interval: ColonSeparatedDuration,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_delimiter = ',',
+        help = "Announce when this many seconds remain, with a banner and the bell (e.g., \"300,60,10\")"
+    )]
+    announce: Vec<u64>,
+    #[cfg(feature = "notify")]
+    #[arg(long, help = "Show a desktop notification when the timer finishes")]
+    notify: bool,
+    #[arg(
+        long,
+        value_name = "CMD",
+        help = "Run a shell command when the timer finishes (not when it's stopped), with TIMER_OUTCOME, TIMER_ELAPSED and TIMER_LABEL in its environment"
+    )]
+    exec: Option<String>,
+    #[arg(
+        long,
+        requires = "exec",
+        help = "Also run the --exec command when the timer is stopped early, with TIMER_OUTCOME set to \"stopped\""
+    )]
+    exec_on_stop: bool,
+    #[cfg(feature = "sound")]
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Play a wav or mp3 file when the timer finishes"
+    )]
+    sound: Option<std::path::PathBuf>,
+    #[cfg(feature = "sound")]
+    #[arg(
+        long,
+        requires = "sound",
+        help = "Keep playing the sound until a key is pressed"
+    )]
+    sound_loop: bool,
+    #[arg(
+        long,
+        help = "Print the status as a line of JSON to stdout every second instead of drawing in the terminal"
+    )]
+    json: bool,
+    #[arg(
+        long,
+        conflicts_with = "json",
+        help = "Wait for the timer without any output"
+    )]
+    quiet: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Keep the current time in this file for status bars, writing \"done\" when the timer finishes"
+    )]
+    status_file: Option<std::path::PathBuf>,
+    #[cfg(unix)]
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["json", "quiet"],
+        help = "Send the status as lines of JSON, like --json, to every client of a Unix socket created at this path"
+    )]
+    socket: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        help = "Show the current time in the terminal title, e.g., on the taskbar"
+    )]
+    set_title: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "quiet"],
+        help = "Show the progress on the taskbar in terminals that support it, like Windows Terminal"
+    )]
+    taskbar_progress: bool,
+    #[arg(
+        long,
+        value_name = "TEXT",
+        conflicts_with = "from_file",
+        help = "Name the timer, showing the name above the running display and in the messages"
+    )]
+    label: Option<String>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Save the remaining time of a single timer to this file when it is stopped with the quit key or Ctrl+C, deleting it when the timer finishes"
+    )]
+    save_state: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["[[[[w:]d:]h:]m:]s duration", "until", "until_datetime", "from_file", "count_up"],
+        help = "Continue a timer saved with --save-state, saving it back to the same file unless --save-state is given"
+    )]
+    resume: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        help = "Append the finished or stopped timer to the history log in the data directory (e.g., ~/.local/share/timer-cli/history.jsonl)"
+    )]
+    log: bool,
+    #[arg(
+        long,
+        help = "Print the parsed durations to stdout and exit without starting the timer"
+    )]
+    dry_run: bool,
+    #[arg(
+        long,
+        help = "Print examples of the accepted duration formats and exit"
+    )]
+    examples: bool,
+    #[arg(
+        long,
+        help = "Print only the version number (e.g., \"0.1.2\") for scripts, unlike --version"
+    )]
+    version_raw: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "quiet"],
+        help = "Wait for a key press after the timer finishes instead of exiting right away"
+    )]
+    wait_on_finish: bool,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        conflicts_with_all = ["json", "quiet", "every", "wait_on_finish"],
+        help = "Offer to snooze once the timer finishes, counting down DURATION again whenever s is pressed, like an alarm clock"
+    )]
+    snooze: Option<ColonSeparatedDuration>,
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "quiet"],
+        help = "Leave out the message printed when the timer finishes or is stopped, e.g., when --exec or --notify already report it"
+    )]
+    no_message: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "quiet"],
+        help = "Clear the screen when the timer exits, or only the timer's line with --inline, before the finished or stopped message"
+    )]
+    clear_on_exit: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "quiet"],
+        help = "Start the first timer paused, so that it only begins once the pause key is pressed"
+    )]
+    pause_on_start: bool,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        conflicts_with_all = ["json", "quiet"],
+        help = "Wait this long before the first timer starts, counting down \"Starting in ...\", which the quit key cancels (e.g., \"10\" for 10 seconds)"
+    )]
+    delay: Option<ColonSeparatedDuration>,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        conflicts_with_all = ["json", "quiet"],
+        help = "Count down this long between timers run one after another, showing \"Next: ... in ...\", which the pause key holds and the quit key stops (e.g., \"10\" for 10 seconds)"
+    )]
+    gap: Option<ColonSeparatedDuration>,
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "quiet"],
+        help = "Draw the timer on the current line instead of the alternate screen, leaving the last line in the scrollback"
+    )]
+    inline: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "quiet", "inline", "wait_on_finish", "snooze"],
+        help = "Print the remaining time on a new line every second instead of taking over the terminal, for terminals without raw mode, which is also the fallback when enabling it fails"
+    )]
+    no_raw_mode: bool,
+    #[arg(
+        long,
+        value_name = "stdout|stderr",
+        default_value = "stderr",
+        help = "Draw the timer on stdout or stderr"
+    )]
+    output: OutputStream,
+    #[arg(
+        long,
+        conflicts_with_all = ["json", "quiet"],
+        help = "Also read the pause, quit and reset keys from stdin, one per line, to control the timer from a script"
+    )]
+    stdin_commands: bool,
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "30",
+        conflicts_with_all = ["json", "quiet", "inline"],
+        help = "Print the remaining time on a new line every INTERVAL for screen readers, instead of redrawing the screen, and read the keys as typed commands followed by Enter"
+    )]
+    accessible: Option<ColonSeparatedDuration>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    #[command(
+        about = "Print the time spent on timers today and this week, and how many were completed or stopped, from the history log kept with --log"
+    )]
+    Stats,
+    #[command(
+        about = "Continue a timer saved with --save-state, the same as --resume, taking the options before the command"
+    )]
+    Resume {
+        #[arg(value_name = "PATH", help = "The file the timer was saved to")]
+        path: std::path::PathBuf,
+    },
+}
+
+/// The exit code of a timer stopped before it ran out, e.g., with `q` or Ctrl+C,
+/// following the shell convention of 128 + SIGINT.
+const INTERRUPTED_EXIT_CODE: u8 = 130;
+
+/// The keys that control a running timer.
+#[derive(Debug, Clone, Copy)]
+struct KeyBindings {
+    pause: char,
+    quit: char,
+    reset: char,
+    restart: char,
+}
+
+impl KeyBindings {
+    /// Keys with a fixed action, with what they do, so they can't be bound to anything else.
+    const RESERVED_KEYS: &[(char, &str)] = &[
+        ('+', "adjusts the remaining time"),
+        ('=', "adjusts the remaining time"),
+        ('-', "adjusts the remaining time"),
+        ('d', "cycles the details shown"),
+        ('l', "records a lap"),
+        ('e', "types a new remaining time"),
+        (SNOOZE_KEY, "snoozes a finished timer"),
+        (MUTE_KEY, "mutes the finishing sounds"),
+    ];
+
+    fn new(pause: char, quit: char, reset: char, restart: char) -> Result<Self, Error> {
+        let bindings = [
+            ("pause", pause),
+            ("quit", quit),
+            ("reset", reset),
+            ("restart", restart),
+        ];
+        for (i, &(action, key)) in bindings.iter().enumerate() {
+            if let Some((other, _)) = bindings[..i].iter().find(|&&(_, other)| other == key) {
+                return Err(user(
+                    &format!(
+                        "The {other} and {action} actions are both bound to {}",
+                        KeyDisplay(key)
+                    ),
+                    "Bind each action to a different key",
+                ));
+            }
+            if let Some((_, fixed)) = Self::RESERVED_KEYS
+                .iter()
+                .find(|&&(reserved, _)| reserved == key)
+            {
+                let reserved = Self::RESERVED_KEYS
+                    .iter()
+                    .map(|&(reserved, _)| KeyDisplay(reserved).to_string())
+                    .collect::<Vec<_>>();
+                let (last, rest) = reserved.split_last().expect("keys are reserved");
+                return Err(user(
+                    &format!(
+                        "The {action} action is bound to {}, which {fixed}",
+                        KeyDisplay(key)
+                    ),
+                    &format!(
+                        "Bind the action to a key other than {} and {last}",
+                        rest.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        Ok(Self {
+            pause,
+            quit,
+            reset,
+            restart,
+        })
+    }
+
+    fn paused_hint(self) -> String {
+        format!(
+            "Timer is paused. Press {} to resume or {} to quit.",
+            KeyDisplay(self.pause),
+            KeyDisplay(self.quit),
+        )
+    }
+}
+
+/// A key as shown to the user, like 'p' or space.
+#[derive(Debug, Clone, Copy)]
+struct KeyDisplay(char);
+
+impl fmt::Display for KeyDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ' ' => f.write_str("space"),
+            key => write!(f, "'{key}'"),
+        }
+    }
+}
+
+/// Picks the durations from the positional arguments, `--until` or `--until-datetime`, or the
+/// environment, in that order.
+///
+/// A duration is `None` only for a stopwatch without a cap.
+fn resolve_durations(
+    durations: Vec<ColonSeparatedDuration>,
+    until: Option<Until>,
+    mode: Mode,
+) -> Result<Vec<Option<Duration>>, Error> {
+    match (durations.is_empty(), until) {
+        (false, Some(_)) => Err(user(
+            "Both a duration and --until were provided",
+            "Provide either a duration or --until, but not both",
+        )),
+        (false, None) => Ok(durations
+            .into_iter()
+            .map(|ColonSeparatedDuration(duration)| Some(duration))
+            .collect()),
+        (true, Some(Until::TimeOfDay(time))) => time
+            .duration_from_now()
+            .map(|duration| vec![Some(duration)]),
+        (true, Some(Until::DateTime(datetime))) => datetime
+            .duration_from(Local::now())
+            .map(|duration| vec![Some(duration)]),
+        (true, None) if mode == Mode::CountUp && std::env::var_os(DURATION_ENV_VAR).is_none() => {
+            Ok(vec![None])
+        }
+        (true, None) => duration_from_env().map(|duration| vec![Some(duration)]),
+    }
+}
+
+/// Prints each step with its duration normalized, like "1h 2m 3s (3723s)", for `--dry-run`.
+fn print_steps(steps: &[Step]) {
+    let describe = |duration: Duration| {
+        // Only show the milliseconds if there are any
+        let precision = if duration.subsec_nanos() == 0 { 0 } else { 3 };
+        // Not through a float, which would round the seconds of very long durations
+        let seconds = match duration.subsec_nanos() {
+            0 => duration.as_secs().to_string(),
+            nanos => format!("{}.{nanos:09}", duration.as_secs())
+                .trim_end_matches('0')
+                .to_owned(),
+        };
+        format!("{:.*} ({seconds}s)", precision, DurationDisplay(duration))
+    };
+    for step in steps {
+        let duration = match step.duration {
+            Some(duration) => describe(duration),
+            None => "no limit".to_owned(),
+        };
+        match &step.label {
+            Some(label) => println!("{label}: {duration}"),
+            None => println!("{duration}"),
+        }
+    }
+    if steps.len() > 1
+        && let Some(total) = steps.iter().try_fold(Duration::ZERO, |total, step| {
+            total.checked_add(step.duration?)
+        })
+    {
+        println!("Total: {}", describe(total));
+    }
+}
+
+/// Examples of each duration format, under the name of the format.
+const DURATION_EXAMPLES: &[(&str, &[&str])] = &[
+    (
+        "Colon-separated ([[[[w:]d:]h:]m:]s)",
+        &[
+            "90",
+            "5:00",
+            "1:30:00",
+            "1:0:0:0",
+            "1:0:0:0:0",
+            "1::30",
+            "2:30.5",
+        ],
+    ),
+    ("Unit suffixes", &["45s", "25m", "1h30m15s", "1.5h", "2w1d"]),
+    (
+        "Unit words (quoted as one argument)",
+        &["5 minutes", "1 hour 30 min", "2 days 4 hrs"],
+    ),
+    ("ISO 8601", &["PT1H30M15S", "P1DT2H", "PT0.5S"]),
+];
+
+/// Prints [`DURATION_EXAMPLES`] along with the durations they stand for.
+fn print_examples() {
+    for (index, (format, examples)) in DURATION_EXAMPLES.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("{format}:");
+        for example in *examples {
+            let duration = parse_duration(example).expect("the examples are valid");
+            // Only show the milliseconds if there are any
+            let precision = if duration.subsec_nanos() == 0 { 0 } else { 3 };
+            let example = if example.contains(' ') {
+                format!("\"{example}\"")
+            } else {
+                example.to_string()
+            };
+            println!("  {example:<18}{:.*}", precision, DurationDisplay(duration));
+        }
+    }
+}
+
+/// Rejects the steps if any of them is longer than `--max`.
+fn reject_long_durations(steps: Vec<Step>, max: Option<Duration>) -> Result<Vec<Step>, Error> {
+    let Some(max) = max else {
+        return Ok(steps);
+    };
+    let Some(duration) = find_longer_duration(&steps, max) else {
+        return Ok(steps);
+    };
+
+    // An extra part or a few extra digits most likely shifted the duration by a unit or more,
+    // while a duration less than a unit over the maximum is only too long
+    let mut suggestion = Duration::ZERO;
+    if duration >= max.saturating_mul(60) {
+        suggestion = duration;
+        while suggestion > max {
+            suggestion /= 60;
+        }
+    }
+    let advice = if suggestion.is_zero() {
+        format!("Provide a duration of at most {}", DurationDisplay(max))
+    } else {
+        format!(
+            "Provide a duration of at most {}, like {} if that's what you meant",
+            DurationDisplay(max),
+            DurationDisplay(suggestion),
+        )
+    };
+    Err(user_with_cause(
+        "The duration is longer than the maximum",
+        &advice,
+        user(
+            &format!(
+                "{} is longer than the maximum of {} set with --max",
+                DurationDisplay(duration),
+                DurationDisplay(max),
+            ),
+            "Make sure the duration doesn't have an extra part or digit",
+        ),
+    ))
+}
+
+/// The first duration of the steps that is longer than `threshold`.
+fn find_longer_duration(steps: &[Step], threshold: Duration) -> Option<Duration> {
+    steps
+        .iter()
+        .filter_map(|step| step.duration)
+        .find(|&duration| duration > threshold)
+}
+
+/// A countdown of zero would end right away, so only a stopwatch may have a zero cap.
+fn reject_zero_durations(steps: Vec<Step>, mode: Mode) -> Result<Vec<Step>, Error> {
+    if mode == Mode::CountDown
+        && steps
+            .iter()
+            .any(|step| step.duration == Some(Duration::ZERO))
+    {
+        return Err(user(
+            "The duration is zero",
+            "Provide a positive duration, or use --count-up for a stopwatch",
+        ));
+    }
+    Ok(steps)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Repeat {
+    Times(u32),
+    Infinite,
+}
+
+impl Repeat {
+    /// Whether the given 1-based repetition is the last one to run.
+    fn is_last(self, repetition: u32) -> bool {
+        match self {
+            Repeat::Times(times) => repetition >= times,
+            Repeat::Infinite => false,
+        }
+    }
+}
+
+impl FromStr for Repeat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("infinite") {
+            return Ok(Self::Infinite);
+        }
+        s.parse().map(Self::Times).map_err(|err| {
+            user_with_internal(
+                "Failed to parse the repeat count",
+                "Provide a non-negative number of repetitions or \"infinite\"",
+                err,
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Counts the remaining time down to zero.
+    CountDown,
+    /// Counts the elapsed time up from zero.
+    CountUp,
+}
+
+const DURATION_ENV_VAR: &str = "TIMER_DURATION";
+
+/// Reads the duration from the [`DURATION_ENV_VAR`] environment variable,
+/// used when no duration is passed on the command line.
+fn duration_from_env() -> Result<Duration, Error> {
+    match std::env::var(DURATION_ENV_VAR) {
+        Ok(value) => value
+            .parse::<ColonSeparatedDuration>()
+            .map(|ColonSeparatedDuration(duration)| duration)
+            .map_err(|err| {
+                user_with_cause(
+                    &format!("Failed to parse the {DURATION_ENV_VAR} environment variable"),
+                    &format!("Make sure {DURATION_ENV_VAR} contains a valid duration"),
+                    err,
+                )
+            }),
+        Err(std::env::VarError::NotPresent) => Err(user(
+            "No duration provided",
+            &format!(
+                "Pass the duration as an argument (e.g., \"timer 5:00\") or set the {DURATION_ENV_VAR} environment variable"
+            ),
+        )),
+        Err(err @ std::env::VarError::NotUnicode(_)) => Err(user_with_internal(
+            &format!("Failed to read the {DURATION_ENV_VAR} environment variable"),
+            &format!("Make sure {DURATION_ENV_VAR} contains a valid duration"),
+            err,
+        )),
+    }
+}
+
+/// A range of durations to pick a random one from, like "1:00..5:00".
+#[derive(Debug, Clone, Copy)]
+struct DurationRange {
+    min: Duration,
+    max: Duration,
+}
+
+impl DurationRange {
+    /// Picks a duration uniformly from the range, to the millisecond.
+    fn sample(self) -> Duration {
+        let span = u64::try_from((self.max - self.min).as_millis()).unwrap_or(u64::MAX);
+        self.min
+            .saturating_add(Duration::from_millis(fastrand::u64(0..=span)))
+            .min(self.max)
+    }
+}
+
+impl FromStr for DurationRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ADVICE: &str =
+            "Provide the range as two durations separated by \"..\" (e.g., \"1:00..5:00\")";
+        let Some((min, max)) = s.split_once("..") else {
+            return Err(user_with_cause(
+                "Failed to parse the range of durations",
+                ADVICE,
+                user("Missing \"..\"", "Separate the two durations with \"..\""),
+            ));
+        };
+        let min = parse_duration(min)
+            .map_err(|err| user_with_cause("Failed to parse the shortest duration", ADVICE, err))?;
+        let max = parse_duration(max)
+            .map_err(|err| user_with_cause("Failed to parse the longest duration", ADVICE, err))?;
+        if min.is_zero() {
+            return Err(user(
+                "The shortest duration is zero",
+                "Provide a shortest duration above zero (e.g., \"1..5:00\")",
+            ));
+        }
+        if min > max {
+            return Err(user(
+                "The shortest duration is longer than the longest one",
+                "Put the shorter duration first (e.g., \"1:00..5:00\")",
+            ));
+        }
+        Ok(Self { min, max })
+    }
+}
+
+/// A timer to start after the others with `--then`, like "5:00" or "Break=5:00".
+#[derive(Debug, Clone)]
+struct ChainedTimer(Step);
+
+impl FromStr for ChainedTimer {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (label, duration) = match s.rsplit_once('=') {
+            Some((label, duration)) => (Some(label.trim()), duration),
+            None => (None, s),
+        };
+        if label == Some("") {
+            return Err(user(
+                "The label of the timer is empty",
+                "Put a label before the \"=\" (e.g., \"Break=5:00\"), or leave out the \"=\"",
+            ));
+        }
+        let duration = parse_duration(duration).map_err(|err| {
+            user_with_cause(
+                "Failed to parse the duration of the timer",
+                "Provide a duration, optionally after a label and \"=\" (e.g., \"Break=5:00\")",
+                err,
+            )
+        })?;
+        Ok(Self(Step {
+            label: label.map(str::to_owned),
+            duration: Some(duration),
+        }))
+    }
+}
+
+/// A local wall-clock time of day, like "17:00", "17:00:30" or "5:00 PM".
+#[derive(Debug, Clone, Copy)]
+struct TimeOfDay(NaiveTime);
+
+impl TimeOfDay {
+    /// The time left until the next occurrence of this time of day,
+    /// rolling over to tomorrow if it has already passed today.
+    fn duration_from_now(self) -> Result<Duration, Error> {
+        let now = Local::now();
+        let mut target = now.date_naive().and_time(self.0);
+        if target <= now.naive_local() {
+            target += TimeDelta::days(1);
+        }
+        duration_until(local_time(target)?, now)
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        NaiveTime::parse_from_str(s, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+            .or_else(|_| NaiveTime::parse_from_str(s, "%I:%M:%S %p"))
+            .or_else(|_| NaiveTime::parse_from_str(s, "%I:%M %p"))
+            .map(Self)
+            .map_err(|err| {
+                user_with_internal(
+                    "Failed to parse the time of day",
+                    "Provide the time on the 24-hour clock as \"HH:MM[:SS]\" (e.g., \"17:00\") or on the 12-hour clock as \"HH:MM[:SS] AM|PM\" (e.g., \"5:00 PM\")",
+                    err,
+                )
+            })
+    }
+}
+
+/// A local date and time, like "2025-12-31 23:59" or "2025-12-31 23:59:30".
+#[derive(Debug, Clone, Copy)]
+struct LocalDateTime(NaiveDateTime);
+
+impl LocalDateTime {
+    /// The time left from `now` until this date and time, which must be in the future.
+    fn duration_from(self, now: DateTime<Local>) -> Result<Duration, Error> {
+        let target = local_time(self.0)?;
+        if target <= now {
+            return Err(user_with_cause(
+                "The target date and time has already passed",
+                "Provide a date and time in the future, or use --until for a time of day",
+                user(
+                    &format!(
+                        "{} is before the current time of {}",
+                        self.0.format("%Y-%m-%d %H:%M:%S"),
+                        now.format("%Y-%m-%d %H:%M:%S"),
+                    ),
+                    "Make sure the date and the year are right",
+                ),
+            ));
+        }
+        duration_until(target, now)
+    }
+}
+
+impl FromStr for LocalDateTime {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+            .into_iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+            .map(Self)
+            .ok_or_else(|| {
+                user(
+                    "Failed to parse the date and time",
+                    "Provide the local date and time as \"YYYY-MM-DD HH:MM[:SS]\" on the 24-hour clock (e.g., \"2026-12-31 23:59\")",
+                )
+            })
+    }
+}
+
+/// The target of `--until` or `--until-datetime`.
+#[derive(Debug, Clone, Copy)]
+enum Until {
+    TimeOfDay(TimeOfDay),
+    DateTime(LocalDateTime),
+}
+
+/// Places a wall-clock time in the local time zone, picking the earlier one of an ambiguous time.
+fn local_time(target: NaiveDateTime) -> Result<DateTime<Local>, Error> {
+    target.and_local_timezone(Local).earliest().ok_or_else(|| {
+        user(
+            "The target time does not exist in the local time zone",
+            "Pick a time outside of the daylight saving time transition",
+        )
+    })
+}
+
+/// The time left from `now` until `target`, rounded to whole seconds.
+fn duration_until(target: DateTime<Local>, now: DateTime<Local>) -> Result<Duration, Error> {
+    let duration = (target - now).to_std().map_err(|err| {
+        system_with_internal(
+            "Failed to compute the time until the target",
+            "Try notifying the developer",
+            err,
+        )
+    })?;
+    // The countdown ticks in whole seconds, so round to the nearest one
+    Ok(Duration::from_secs(
+        duration.as_secs() + u64::from(duration.subsec_millis() >= 500),
+    ))
+}
+
+// Rows of the running display, below the header if there is one
+const TIME_ROW: u16 = 0;
+/// The elapsed time of a countdown, or the remaining time of a capped stopwatch.
+const OTHER_TIME_ROW: u16 = 1;
+const END_TIME_ROW: u16 = 2;
+const PROGRESS_BAR_ROW: u16 = 3;
+const PAUSED_ROW: u16 = 4;
+const PAUSED_HINT_ROW: u16 = 5;
+const MILESTONE_ROW: u16 = 6;
+/// The most recent lap of a stopwatch.
+const LAP_ROW: u16 = 7;
+const EDIT_ROW: u16 = 8;
+const EDIT_ERROR_ROW: u16 = 9;
+/// Whether the mute key just silenced the timer or turned the sound back on.
+const MUTE_ROW: u16 = 10;
+
+/// How the timer presents itself.
+#[derive(Debug, Clone, Copy)]
+enum Interface {
+    Terminal,
+    Json,
+    Quiet,
+}
+
+/// How much of the running display is shown, cycled through with the `d` key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Detail {
+    /// The time with its percentage, the other time, the end time and the progress bar.
+    #[default]
+    Full,
+    /// Only the time.
+    Time,
+    /// The time with its percentage.
+    Percent,
+}
+
+impl Detail {
+    fn next(self) -> Self {
+        match self {
+            Detail::Full => Detail::Time,
+            Detail::Time => Detail::Percent,
+            Detail::Percent => Detail::Full,
+        }
+    }
+}
+
+/// The choices made with keys while a timer runs, kept from one step to the next.
+#[derive(Debug, Clone, Copy, Default)]
+struct Preferences {
+    detail: Detail,
+    /// Whether the bell, notification and sound are silenced with the mute key.
+    muted: bool,
+}
+
+/// How a timer run ended.
+#[derive(Debug, Clone, Copy)]
+enum Outcome {
+    /// The timer ran out after the given time.
+    Completed(Duration),
+    /// The user stopped the timer after it ran for the given time.
+    Interrupted(Duration),
+    /// The user asked to start the whole sequence over.
+    Reset,
+}
+
+/// Names a timer in messages, like `Timer "Tea"`, or just `Timer` without a label.
+#[derive(Debug, Clone, Copy)]
+struct Named<'a> {
+    noun: &'static str,
+    label: Option<&'a str>,
+}
+
+impl<'a> Named<'a> {
+    fn timer(label: &'a Option<String>) -> Self {
+        Self {
+            noun: "Timer",
+            label: label.as_deref(),
+        }
+    }
+
+    fn stopwatch(label: &'a Option<String>) -> Self {
+        Self {
+            noun: "Stopwatch",
+            label: label.as_deref(),
+        }
+    }
+}
+
+impl fmt::Display for Named<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.label {
+            Some(label) => write!(f, "{} \"{label}\"", self.noun),
+            None => f.write_str(self.noun),
+        }
+    }
+}
+
+/// One of the timers to run one after another.
+#[derive(Debug, Clone)]
+struct Step {
+    label: Option<String>,
+    /// `None` only for a stopwatch without a cap.
+    duration: Option<Duration>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.label, self.duration) {
+            (Some(label), Some(duration)) => write!(f, "{label} ({})", DurationDisplay(duration)),
+            (Some(label), None) => f.write_str(label),
+            (None, Some(duration)) => write!(f, "{}", DurationDisplay(duration)),
+            (None, None) => f.write_str("stopwatch"),
+        }
+    }
+}
+
+/// Where a run is in the sequence of steps.
+#[derive(Debug, Clone, Copy)]
+struct Segment<'a> {
+    /// The 1-based position of the run.
+    number: usize,
+    count: usize,
+    label: Option<&'a str>,
+    /// The following step, if any.
+    next: Option<&'a Step>,
+}
+
+impl Segment<'_> {
+    fn is_last(self) -> bool {
+        self.number == self.count
+    }
+
+    /// The line shown above the running display, if there's a label or more than one run.
+    fn header(self) -> Option<String> {
+        let position = format!("{} of {}", self.number, self.count);
+        match (self.label, self.count, self.next) {
+            (None, ..=1, _) => None,
+            (Some(label), ..=1, _) => Some(label.to_owned()),
+            (Some(label), _, Some(next)) => {
+                Some(format!("{label} (segment {position}), next up: {next}"))
+            }
+            (Some(label), _, None) => Some(format!("{label} (segment {position})")),
+            (None, _, Some(next)) => Some(format!("Segment {position}, next up: {next}")),
+            (None, _, None) => Some(format!("Segment {position}")),
+        }
+    }
+}
+
+/// Runs the steps one after another, stopping early if the user quits.
+///
+/// The bell, notification, sound and command only fire once the last step runs out.
+async fn run_sequence(
+    steps: &[Step],
+    options: &TimerOptions,
+    interface: Interface,
+) -> Result<Outcome, Error> {
+    let mut commands = StdinCommands::new(options.stdin_commands);
+    // Listen for the whole sequence, so that the signal isn't lost between the steps
+    let mut terminate = TerminateSignal::new().map_err(|err| {
+        system_with_internal(
+            "Failed to listen for SIGTERM",
+            "Try notifying the developer",
+            err,
+        )
+    })?;
+    let mut summary = Summary::new(steps, options.repeat).with_gap(options.gap);
+    let mut preferences = Preferences::default();
+    let mut total = Duration::ZERO;
+    let mut index = 0;
+    while let Some(step) = steps.get(index) {
+        let segment = Segment {
+            number: index + 1,
+            count: steps.len(),
+            label: step.label.as_deref(),
+            next: steps.get(index + 1),
+        };
+        let outcome = match interface {
+            Interface::Terminal => {
+                run_timer(
+                    step.duration,
+                    options,
+                    segment,
+                    &mut preferences,
+                    &mut commands,
+                    &mut terminate,
+                )
+                .await?
+            }
+            Interface::Json => run_json(step.duration, options, &mut terminate).await?,
+            Interface::Quiet => run_quiet(step.duration, options, &mut terminate).await?,
+        };
+        match outcome {
+            Outcome::Completed(elapsed) => {
+                total = total.saturating_add(elapsed);
+                summary.complete(index, elapsed);
+            }
+            Outcome::Interrupted(elapsed) => {
+                if let (Interface::Terminal, 2..) = (interface, steps.len()) {
+                    summary.stop(index, elapsed);
+                    print_summary(&summary, options)?;
+                }
+                return Ok(Outcome::Interrupted(total.saturating_add(elapsed)));
+            }
+            Outcome::Reset => {
+                total = Duration::ZERO;
+                index = 0;
+                summary.clear();
+                continue;
+            }
+        }
+        index += 1;
+
+        if let (Interface::Terminal, Some(next)) = (interface, segment.next) {
+            let mut output = options.output.writer();
+            let bell = options.bell_count > 0 && !preferences.muted && output.is_tty();
+            crossterm::execute!(
+                output,
+                style::Print(format_args!(
+                    "Segment {} of {} finished, next up: {next}\n",
+                    segment.number, segment.count,
+                )),
+                style::Print(if bell { "\x07" } else { "" }),
+            )
+            .map_err(|err| {
+                system_with_internal(
+                    "Failed to write to the terminal",
+                    "Try notifying the developer",
+                    err,
+                )
+            })?;
+            if let Some(gap) = options.gap {
+                let outcome = run_gap(gap, next, options, &mut commands, &mut terminate).await?;
+                if let Outcome::Completed(elapsed) | Outcome::Interrupted(elapsed) = outcome {
+                    summary.gap(elapsed);
+                }
+                if let Outcome::Interrupted(_) = outcome {
+                    if !options.no_message {
+                        use std::io::Write;
+
+                        writeln!(
+                            output,
+                            "{} cancelled before it started.",
+                            Named::timer(&next.label)
+                        )
+                        .map_err(|err| {
+                            system_with_internal(
+                                "Failed to write to the terminal",
+                                "Try notifying the developer",
+                                err,
+                            )
+                        })?;
+                    }
+                    print_summary(&summary, options)?;
+                    return Ok(Outcome::Interrupted(total));
+                }
+            }
+        }
+    }
+
+    if let (Interface::Terminal, 2..) = (interface, steps.len()) {
+        print_summary(&summary, options)?;
+    }
+    if let Some(path) = &options.save_state
+        && let Err(e) = SavedTimer::remove(path)
+    {
+        eprintln!("{e}");
+    }
+    finish(
+        &mut options.output.writer(),
+        total,
+        options,
+        preferences.muted,
+        &mut terminate,
+    )
+    .await?;
+    if let (Interface::Terminal, Some(snooze)) = (interface, options.snooze) {
+        while wait_for_snooze(
+            &mut options.output.writer(),
+            snooze,
+            options,
+            &mut terminate,
+        )
+        .await?
+        {
+            let segment = Segment {
+                number: 1,
+                count: 1,
+                label: options.label.as_deref(),
+                next: None,
+            };
+            let outcome = run_timer(
+                Some(snooze),
+                options,
+                segment,
+                &mut preferences,
+                &mut commands,
+                &mut terminate,
+            )
+            .await?;
+            match outcome {
+                Outcome::Completed(elapsed) => {
+                    total = total.saturating_add(elapsed);
+                    finish(
+                        &mut options.output.writer(),
+                        total,
+                        options,
+                        preferences.muted,
+                        &mut terminate,
+                    )
+                    .await?;
+                }
+                Outcome::Interrupted(elapsed) => {
+                    return Ok(Outcome::Interrupted(total.saturating_add(elapsed)));
+                }
+                // Only a later segment of a sequence starts it over
+                Outcome::Reset => {}
+            }
+        }
+    }
+    if options.wait_on_finish {
+        wait_for_key(
+            &mut options.output.writer(),
+            "Finished, press any key to exit.",
+            &mut terminate,
+        )
+        .await?;
+    }
+    Ok(Outcome::Completed(total))
+}
+
+/// Prints the table of the steps at the end of a sequence, on the `--output` stream.
+fn print_summary(summary: &Summary, options: &TimerOptions) -> Result<(), Error> {
+    print_output(options, format_args!("\n{summary}"))
+}
+
+/// Prints to the `--output` stream, which [`run_lines`] writes all of its lines to.
+fn print_output(options: &TimerOptions, args: fmt::Arguments<'_>) -> Result<(), Error> {
+    use std::io::Write;
+
+    options.output.writer().write_fmt(args).map_err(|err| {
+        system_with_internal(
+            "Failed to write to the terminal",
+            "Try notifying the developer",
+            err,
+        )
+    })
+}
+
+/// Set once the terminal couldn't be taken over, so that the following steps go straight to
+/// [`run_lines`].
+static RAW_MODE_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Runs the timer until the duration runs out or the user quits.
+///
+/// A stopwatch without a duration runs until the user quits.
+/// Falls back to [`run_lines`] when the output isn't a terminal, in the accessible mode,
+/// with `--no-raw-mode`, or when the terminal can't be taken over.
+async fn run_timer(
+    duration: Option<Duration>,
+    options: &TimerOptions,
+    segment: Segment<'_>,
+    preferences: &mut Preferences,
+    commands: &mut StdinCommands,
+    terminate: &mut TerminateSignal,
+) -> Result<Outcome, Error> {
+    if options.accessible
+        || options.no_raw_mode
+        || RAW_MODE_FAILED.load(Ordering::Relaxed)
+        || !options.output.writer().is_tty()
+    {
+        return run_lines(duration, options, segment, commands, terminate).await;
+    }
+    let writer = if options.inline {
+        TerminalGuard::enter_inline(options.output.writer())
+    } else {
+        TerminalGuard::enter(options.output.writer())
+    };
+    let mut writer = match writer {
+        Ok(writer) => writer,
+        Err(err) => {
+            // Only warn once, without trying again for the following steps
+            RAW_MODE_FAILED.store(true, Ordering::Relaxed);
+            eprintln!(
+                "{}",
+                system_with_internal(
+                    "Failed to take over the terminal, printing the time as plain lines instead",
+                    "Pass --no-raw-mode to skip taking over the terminal",
+                    err,
+                )
+            );
+            return run_lines(duration, options, segment, commands, terminate).await;
+        }
+    };
+    writer.clear_on_restore(options.clear_on_exit);
+    writer.clear_taskbar_progress_on_restore(options.taskbar_progress);
+    let mut event_stream = EventStream::new();
+
+    if segment.number == 1
+        && let Some(delay) = options.delay
+        && let Outcome::Interrupted(_) = run_countdown(
+            &mut writer,
+            delay,
+            "Starting",
+            options,
+            &mut event_stream,
+            commands,
+            terminate,
+        )
+        .await?
+    {
+        writer
+            .restore()
+            .and_then(|_| {
+                use std::io::Write;
+
+                if options.no_message {
+                    return Ok(());
+                }
+                writeln!(
+                    writer,
+                    "{} cancelled before it started.",
+                    Named::timer(&segment.label.map(str::to_owned))
+                )
+            })
+            .map_err(|err| {
+                system_with_internal(
+                    "Failed to restore the terminal",
+                    "Try notifying the developer",
+                    err,
+                )
+            })?;
+        return Ok(Outcome::Interrupted(Duration::ZERO));
+    }
+
+    let TimerOptions {
+        mode,
+        repeat,
+        tick_period,
+        ..
+    } = *options;
+    let mut state = TimerState::new(duration, options.clock.now());
+    state.label = segment.label.map(str::to_owned);
+    state.header = segment.header();
+    state.detail = preferences.detail;
+    state.muted = preferences.muted;
+    if state.has_end {
+        state.milestones = Milestones::new(&options.announce, state.initial_duration);
+    }
+    let started_at = Local::now();
+    let mut elapsed = Duration::ZERO;
+    let mut status_file = options.status_file.clone().map(StatusFile::new);
+
+    // Tick on whole periods since the start, so the display changes right on the tick
+    let mut interval = tokio::time::interval_at(
+        options.clock.real(state.started_at),
+        options.clock.real_duration(tick_period),
+    );
+    // The remaining time comes from the deadline, so there's no point in catching up on ticks
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    if options.pause_on_start && segment.number == 1 {
+        state.pause(state.started_at);
+        // Nothing is drawn on the ticks while paused, so draw the display right away
+        render_paused(&mut writer, &mut state, options).map_err(|err| {
+            system_with_internal(
+                "Failed to write to the terminal",
+                "Try notifying the developer",
+                err,
+            )
+        })?;
+    }
+
+    // What the coarse display showed when last drawn
+    let mut coarse_frame = None;
+    // What was last drawn on a tick, to leave an unchanged display alone
+    let mut last_frame: Option<Vec<u8>> = None;
+
+    loop {
+        let event = next_event(&mut event_stream, commands, terminate).fuse();
+        let tick = interval.tick().fuse();
+
+        tokio::select! {
+            maybe_event = event => {
+                let flow = process_event_branch(maybe_event, &mut writer, &mut state, options);
+                // The key may have drawn over the last frame
+                last_frame = None;
+                // Carry the choices over to the next step
+                *preferences = Preferences {
+                    detail: state.detail,
+                    muted: state.muted,
+                };
+                match flow {
+                    ControlFlow::Return(res) => {
+                        if let Some(status_file) = &mut status_file {
+                            status_file.write("");
+                            if let Some(e) = status_file.take_error() {
+                                eprintln!("{e}");
+                            }
+                        }
+                        let elapsed = elapsed + state.running_time(options.clock.now());
+                        if res.is_ok() {
+                            record_stop(started_at, &mut state, elapsed, options);
+                        }
+                        return res.map(|()| Outcome::Interrupted(elapsed));
+                    }
+                    ControlFlow::Break => break,
+                    ControlFlow::Reset if segment.number > 1 => return Ok(Outcome::Reset),
+                    ControlFlow::Reset => {
+                        // The state is already reset, so only the repetitions are left to start over
+                        state.repetition = 1;
+                        elapsed = Duration::ZERO;
+                        let next_tick = next_aligned_tick(&state, options.clock.now(), tick_period);
+                        interval.reset_at(options.clock.real(next_tick));
+                        options.broadcast(StatusEvent::tick(&state));
+                        continue;
+                    }
+                    ControlFlow::Continue => {
+                        // Pausing, resetting and adjusting the time move the whole periods
+                        let next_tick = next_aligned_tick(&state, options.clock.now(), tick_period);
+                        interval.reset_at(options.clock.real(next_tick));
+                        options.broadcast(StatusEvent::tick(&state));
+                        continue;
+                    }
+                }
+            }
+            _ = tick => {
+                let now = options.clock.now();
+                state.update(now);
+                if !state.duration.is_zero() {
+                    options.broadcast(StatusEvent::tick(&state));
+                }
+                if let Some(paused_at) = state.paused_at {
+                    // The inline display is only redrawn when pausing or resuming
+                    if options.inline {
+                        continue;
+                    }
+                    // Blink "PAUSED" once a second, whatever the tick rate
+                    let show = now
+                        .saturating_duration_since(paused_at)
+                        .as_secs()
+                        .is_multiple_of(2);
+                    if show != state.paused_print {
+                        continue;
+                    }
+                    queue_paused_hint(&mut writer, state.top(), options)
+                        .and_then(|_| {
+                            // The end time keeps moving while paused
+                            if state.has_end
+                                && state.detail == Detail::Full
+                                && !options.hidden
+                                && Viewport::current()?.has_row(state.top() + END_TIME_ROW)
+                            {
+                                crossterm::queue!(
+                                    writer,
+                                    cursor::MoveTo(0, state.top() + END_TIME_ROW),
+                                    terminal::Clear(terminal::ClearType::CurrentLine),
+                                    style::Print(format_args!(
+                                        "Ends at {}",
+                                        EndTimeDisplay(state.duration, options.clock_format)
+                                    )),
+                                )?;
+                            }
+                            print_paused(&mut writer, state.top(), &mut state.paused_print)
+                        })
+                        .map_err(|err| {
+                            system_with_internal(
+                                "Failed to write to the terminal",
+                                "Try notifying the developer",
+                                err,
+                            )
+                        })?;
+                    continue;
+                }
+                if state.duration.is_zero() {
+                    if repeat.is_last(state.repetition) {
+                        break;
+                    }
+                    state.repetition += 1;
+                    elapsed += state.elapsed();
+                    state.reset(now);
+                    let bell = if options.bell_count > 0 && !state.muted {
+                        "\x07"
+                    } else {
+                        ""
+                    };
+                    let announcement = match repeat {
+                        _ if options.every => format!("Starting interval {}", state.repetition),
+                        Repeat::Times(times) => {
+                            format!("Starting repetition {} of {times}", state.repetition)
+                        }
+                        Repeat::Infinite => format!("Starting repetition {}", state.repetition),
+                    };
+                    options.broadcast(StatusEvent::Repetition {
+                        repetition: state.repetition,
+                    });
+                    // The announcement replaced the display, which has to be drawn again
+                    coarse_frame = None;
+                    last_frame = None;
+                    if options.inline {
+                        // Leave the finished repetition in the scrollback
+                        crossterm::execute!(
+                            writer,
+                            style::Print("\r\n"),
+                            style::Print(announcement),
+                            style::Print(bell),
+                        )
+                    } else {
+                        crossterm::execute!(
+                            writer,
+                            terminal::BeginSynchronizedUpdate,
+                            terminal::Clear(terminal::ClearType::All),
+                            cursor::MoveTo(0, state.top() + TIME_ROW),
+                            style::Print(announcement),
+                            style::Print(bell),
+                            terminal::EndSynchronizedUpdate,
+                        )
+                    }
+                    .map_err(|err| {
+                        system_with_internal(
+                            "Failed to write to the terminal",
+                            "Try notifying the developer",
+                            err,
+                        )
+                    })?;
+                    if options.every {
+                        remind(options, state.initial_duration, state.muted)?;
+                    }
+                    continue;
+                }
+                if state.milestones.reach(state.duration, now).is_some()
+                    && options.bell_count > 0
+                    && !state.muted
+                {
+                    crossterm::execute!(writer, style::Print('\x07')).map_err(|err| {
+                        system_with_internal(
+                            "Failed to write to the terminal",
+                            "Try notifying the developer",
+                            err,
+                        )
+                    })?;
+                }
+                // A coarse display only changes with the minutes and the banners
+                let frame = duration_style(&state, options).minutes_only.then(|| {
+                    (
+                        DurationDisplay::remaining_minutes(state.duration).0,
+                        state.milestones.banner(now).is_some(),
+                        state.mute_banner(now).is_some(),
+                    )
+                });
+                // Leave the new remaining time being typed alone
+                if state.edit.is_none() && (frame.is_none() || frame != coarse_frame) {
+                    use std::io::Write;
+
+                    let mut buffer = Vec::new();
+                    render(&mut buffer, &state, options)
+                        .and_then(|()| {
+                            if last_frame.as_ref() == Some(&buffer) {
+                                return Ok(());
+                            }
+                            writer.write_all(&buffer)?;
+                            writer.flush()?;
+                            last_frame = Some(buffer);
+                            Ok(())
+                        })
+                        .map_err(|err| {
+                            system_with_internal(
+                                "Failed to write to the terminal",
+                                "Try notifying the developer",
+                                err,
+                            )
+                        })?;
+                }
+                coarse_frame = frame;
+                if let Some(status_file) = &mut status_file {
+                    match mode {
+                        Mode::CountDown if options.hidden => status_file.write("???"),
+                        Mode::CountDown => {
+                            status_file.write(DurationDisplay::remaining(state.duration, false))
+                        }
+                        Mode::CountUp => status_file.write(DurationDisplay(state.elapsed())),
+                    }
+                }
+            }
+        }
+    }
+
+    if options.taskbar_progress {
+        crossterm::execute!(writer, TaskbarProgress::Running(100)).map_err(|err| {
+            system_with_internal(
+                "Failed to write to the terminal",
+                "Try notifying the developer",
+                err,
+            )
+        })?;
+    }
+    if segment.is_last() {
+        // Flash before leaving the alternate screen, so the flash is visible
+        flash(&mut writer, &state, options).await.map_err(|err| {
+            system_with_internal(
+                "Failed to write to the terminal",
+                "Try notifying the developer",
+                err,
+            )
+        })?;
+    }
+
+    // Paused time is already left out, and earlier repetitions are added up
+    let elapsed = elapsed + state.elapsed();
+    options.broadcast(StatusEvent::Finished {
+        elapsed_ms: status::millis(elapsed),
+    });
+    writer
+        .restore()
+        .and_then(|_| {
+            use std::io::Write;
+
+            if !segment.is_last() {
+                return Ok(());
+            }
+            if options.set_title {
+                crossterm::execute!(
+                    writer,
+                    terminal::SetTitle(format_args!("{} finished", Named::timer(&state.label)))
+                )?;
+            }
+            if !options.no_message {
+                writeln!(
+                    writer,
+                    "{} finished after {}!",
+                    Named::timer(&state.label),
+                    finished_time(elapsed, options)
+                )?;
+            }
+            write!(writer, "{}", state.laps)
+        })
+        .map_err(|err| {
+            system_with_internal(
+                "Failed to clear the terminal",
+                "Try notifying the developer",
+                err,
+            )
+        })?;
+
+    if let Some(status_file) = &mut status_file
+        && segment.is_last()
+    {
+        status_file.write("done");
+        if let Some(e) = status_file.take_error() {
+            eprintln!("{e}");
+        }
+    }
+
+    if options.log {
+        log_history(started_at, &state, elapsed, HistoryOutcome::Completed);
+    }
+
+    Ok(Outcome::Completed(elapsed))
+}
+
+/// Counts down the `--gap` before the `next` step, on the current line of the normal screen.
+async fn run_gap(
+    gap: Duration,
+    next: &Step,
+    options: &TimerOptions,
+    commands: &mut StdinCommands,
+    terminate: &mut TerminateSignal,
+) -> Result<Outcome, Error> {
+    let what = format!("Next: {next}");
+    if options.accessible
+        || options.no_raw_mode
+        || RAW_MODE_FAILED.load(Ordering::Relaxed)
+        || !options.output.writer().is_tty()
+    {
+        return wait_countdown_lines(gap, &what, options, commands, terminate).await;
+    }
+    // The timer before has already warned if the terminal can't be taken over
+    let Ok(mut writer) = TerminalGuard::enter_inline(options.output.writer()) else {
+        return wait_countdown_lines(gap, &what, options, commands, terminate).await;
+    };
+    writer.clear_on_restore(true);
+    let outcome = run_countdown(
+        &mut writer,
+        gap,
+        &what,
+        options,
+        &mut EventStream::new(),
+        commands,
+        terminate,
+    )
+    .await?;
+    writer.restore().map_err(|err| {
+        system_with_internal(
+            "Failed to restore the terminal",
+            "Try notifying the developer",
+            err,
+        )
+    })?;
+    Ok(outcome)
+}
+
+/// How long a finished timer ran, to the millisecond for a `--random` duration, which is only
+/// revealed here.
+fn finished_time(elapsed: Duration, options: &TimerOptions) -> String {
+    let precision = if options.hidden && elapsed.subsec_millis() != 0 {
+        3
+    } else {
+        0
+    };
+    format!("{:.*}", precision, DurationDisplay(elapsed))
+}
+
+/// Waits for the `--delay` or `--gap` in [`run_lines`], printing "{what} in ..." once and again
+/// on resuming.
+///
+/// Returns how long it counted, interrupted if the user quit.
+async fn wait_countdown_lines(
+    duration: Duration,
+    what: &str,
+    options: &TimerOptions,
+    commands: &mut StdinCommands,
+    terminate: &mut TerminateSignal,
+) -> Result<Outcome, Error> {
+    let mut countdown = Countdown::new(duration, options.clock.now());
+    print_output(
+        options,
+        format_args!("{what} in {}...\n", DurationDisplay(duration)),
+    )?;
+    loop {
+        let deadline = countdown.deadline();
+        tokio::select! {
+            () = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(options.clock.real(deadline)).await,
+                    None => core::future::pending().await,
+                }
+            } => return Ok(Outcome::Completed(duration)),
+            res = tokio::signal::ctrl_c() => {
+                res.map_err(|err| {
+                    system_with_internal(
+                        "Failed to listen for Ctrl+C",
+                        "Try notifying the developer",
+                        err,
+                    )
+                })?;
+                return Ok(Outcome::Interrupted(countdown.elapsed(options.clock.now())));
+            }
+            () = terminate.recv() => {
+                return Ok(Outcome::Interrupted(countdown.elapsed(options.clock.now())));
+            }
+            command = commands.next() => {
+                let key = command.map_err(|err| {
+                    system_with_internal(
+                        "Failed to read the commands from stdin",
+                        "Make sure stdin is still open",
+                        err,
+                    )
+                })?;
+                let now = options.clock.now();
+                if key == options.keys.quit {
+                    return Ok(Outcome::Interrupted(countdown.elapsed(now)));
+                }
+                if key == options.keys.pause {
+                    countdown.toggle_pause(now);
+                    if countdown.is_paused() {
+                        print_output(options, format_args!("{}\n", options.keys.paused_hint()))?;
+                    } else {
+                        print_output(
+                            options,
+                            format_args!(
+                                "{what} in {}...\n",
+                                DurationDisplay(countdown.remaining(now))
+                            ),
+                        )?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Counts down the `--delay` before the first timer starts, or the `--gap` between two,
+/// showing "{what} in ..." on the current line.
+///
+/// Returns how long it counted, interrupted if the user quit.
+async fn run_countdown(
+    writer: &mut TerminalGuard<Output>,
+    duration: Duration,
+    what: &str,
+    options: &TimerOptions,
+    event_stream: &mut EventStream,
+    commands: &mut StdinCommands,
+    terminate: &mut TerminateSignal,
+) -> Result<Outcome, Error> {
+    let mut countdown = Countdown::new(duration, options.clock.now());
+    let mut interval = tokio::time::interval(options.clock.real_duration(Duration::from_secs(1)));
+    loop {
+        let event = next_event(event_stream, commands, terminate).fuse();
+        let tick = interval.tick().fuse();
+        let deadline = countdown.deadline();
+
+        tokio::select! {
+            () = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(options.clock.real(deadline)).await,
+                    None => core::future::pending().await,
+                }
+            } => return Ok(Outcome::Completed(duration)),
+            maybe_event = event => match maybe_event {
+                Ok(Some(Event::Key(KeyEvent {
+                    code: KeyCode::Char(key),
+                    kind: KeyEventKind::Press,
+                    modifiers,
+                    ..
+                }))) if key == options.keys.quit
+                    || (key == 'c' && modifiers == KeyModifiers::CONTROL) =>
+                {
+                    return Ok(Outcome::Interrupted(countdown.elapsed(options.clock.now())));
+                }
+                Ok(Some(Event::Key(KeyEvent {
+                    code: KeyCode::Char(key),
+                    kind: KeyEventKind::Press,
+                    ..
+                }))) if key == options.keys.pause => {
+                    countdown.toggle_pause(options.clock.now());
+                    // Show the change right away
+                    interval.reset_immediately();
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => return Ok(Outcome::Completed(countdown.elapsed(options.clock.now()))),
+                Err(err) => {
+                    return Err(system_with_internal(
+                        "Failed to read events",
+                        "Try notifying the developer",
+                        err,
+                    ));
+                }
+            },
+            _ = tick => {
+                let remaining = countdown.remaining(options.clock.now());
+                if remaining.is_zero() {
+                    return Ok(Outcome::Completed(duration));
+                }
+                crossterm::execute!(
+                    writer,
+                    terminal::BeginSynchronizedUpdate,
+                    // The same line both inline and on the alternate screen
+                    cursor::MoveToColumn(0),
+                    terminal::Clear(terminal::ClearType::CurrentLine),
+                    style::Print(format_args!(
+                        "{what} in {}...{}",
+                        DurationDisplay::remaining(remaining, false),
+                        if countdown.is_paused() { " (paused)" } else { "" },
+                    )),
+                    terminal::EndSynchronizedUpdate,
+                )
+                .map_err(|err| {
+                    system_with_internal(
+                        "Failed to write to the terminal",
+                        "Try notifying the developer",
+                        err,
+                    )
+                })?;
+            }
+        }
+    }
+}
+
+/// The countdown of the `--delay` or `--gap`, which stands still while paused.
+#[derive(Debug, Clone, Copy)]
+struct Countdown {
+    duration: Duration,
+    /// The time that was left when the countdown was last started or resumed.
+    left: Duration,
+    /// When the countdown was last started or resumed, `None` while it's paused.
+    resumed_at: Option<Instant>,
+}
+
+impl Countdown {
+    fn new(duration: Duration, now: Instant) -> Self {
+        Self {
+            duration,
+            left: duration,
+            resumed_at: Some(now),
+        }
+    }
+
+    fn remaining(&self, now: Instant) -> Duration {
+        match self.resumed_at {
+            Some(resumed_at) => self
+                .left
+                .saturating_sub(now.saturating_duration_since(resumed_at)),
+            None => self.left,
+        }
+    }
+
+    /// How long the countdown has run, leaving out the time it was paused.
+    fn elapsed(&self, now: Instant) -> Duration {
+        self.duration - self.remaining(now)
+    }
+
+    /// When the countdown runs out, unless it's paused.
+    fn deadline(&self) -> Option<Instant> {
+        self.resumed_at.map(|resumed_at| resumed_at + self.left)
+    }
+
+    fn is_paused(&self) -> bool {
+        self.resumed_at.is_none()
+    }
+
+    fn toggle_pause(&mut self, now: Instant) {
+        if self.is_paused() {
+            self.resumed_at = Some(now);
+        } else {
+            self.left = self.remaining(now);
+            self.resumed_at = None;
+        }
+    }
+}
+
+/// Waits for the next terminal event, or for a key read from stdin with `--stdin-commands`.
+///
+/// SIGTERM arrives as Ctrl+C, so that it stops the timer like the user would.
+async fn next_event(
+    event_stream: &mut EventStream,
+    commands: &mut StdinCommands,
+    terminate: &mut TerminateSignal,
+) -> io::Result<Option<Event>> {
+    tokio::select! {
+        maybe_event = event_stream.try_next() => maybe_event,
+        command = commands.next() => command.map(|key| {
+            Some(Event::Key(KeyEvent::new(KeyCode::Char(key), KeyModifiers::NONE)))
+        }),
+        () = terminate.recv() => Ok(Some(Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )))),
+    }
+}
+
+/// Saves a stopped countdown with `--save-state` and appends it to the history log with `--log`,
+/// after it ran for `elapsed` in all.
+///
+/// Failures are only reported, since the timer is stopped anyway.
+fn record_stop(
+    started_at: DateTime<Local>,
+    state: &mut TimerState,
+    elapsed: Duration,
+    options: &TimerOptions,
+) {
+    if let (Some(path), Mode::CountDown, true) = (&options.save_state, options.mode, state.has_end)
+    {
+        state.update(options.clock.now());
+        match SavedTimer::new(state.duration, state.label.clone()).save(path) {
+            Ok(()) => eprintln!(
+                "Saved the timer to {}, continue it with --resume",
+                path.display()
+            ),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+    if options.log {
+        log_history(started_at, state, elapsed, HistoryOutcome::Stopped);
+    }
+}
+
+/// Appends the timer to the history log, only reporting a failure since the timer is over anyway.
+fn log_history(
+    started_at: DateTime<Local>,
+    state: &TimerState,
+    elapsed: Duration,
+    outcome: HistoryOutcome,
+) {
+    let entry = HistoryEntry::new(
+        started_at,
+        state.has_end.then_some(state.initial_duration),
+        elapsed,
+        state.label.as_deref(),
+        outcome,
+    );
+    if let Err(e) = entry.append() {
+        eprintln!("{e}");
+    }
+}
+
+/// Draws a full frame of the running display: the time, and the end time and progress bar if
+/// the timer has an end.
+///
+/// Used both on every tick and to redraw everything after the terminal is resized.
+fn render(
+    writer: &mut impl io::Write,
+    state: &TimerState,
+    options: &TimerOptions,
+) -> io::Result<()> {
+    if options.inline {
+        return render_inline(writer, state, options);
+    }
+    let TimerOptions {
+        mode, ref format, ..
+    } = *options;
+    let duration_style = duration_style(state, options);
+    let viewport = Viewport::current()?;
+    // Without room for both, the time takes the place of the header
+    let top = viewport.top(state.top());
+    crossterm::queue!(
+        writer,
+        terminal::BeginSynchronizedUpdate,
+        terminal::Clear(terminal::ClearType::All),
+    )?;
+    if let (Some(header), 1..) = (&state.header, top) {
+        crossterm::queue!(writer, cursor::MoveTo(0, 0), style::Print(header))?;
+    }
+    crossterm::queue!(writer, cursor::MoveTo(0, top + TIME_ROW))
+        .and_then(|_| queue_time_line(writer, state, options))
+        .and_then(|_| {
+            let other_time = match (format, mode) {
+                _ if state.detail != Detail::Full => None,
+                (None, Mode::CountDown) => Some(format!(
+                    "Elapsed time: {}",
+                    duration_style.display(state.elapsed())
+                )),
+                (None, Mode::CountUp) if state.has_end => Some(format!(
+                    "Remaining time: {}",
+                    duration_style.remaining(state.duration)
+                )),
+                _ => None,
+            };
+            if let Some(other_time) = other_time
+                && viewport.has_row(top + OTHER_TIME_ROW)
+            {
+                crossterm::queue!(
+                    writer,
+                    cursor::MoveTo(0, top + OTHER_TIME_ROW),
+                    style::Print(other_time),
+                )?;
+            }
+            let full = state.has_end && state.detail == Detail::Full && !options.hidden;
+            if full && viewport.has_row(top + END_TIME_ROW) {
+                crossterm::queue!(
+                    writer,
+                    cursor::MoveTo(0, top + END_TIME_ROW),
+                    style::Print(format_args!(
+                        "Ends at {}",
+                        EndTimeDisplay(state.duration, options.clock_format)
+                    )),
+                )?;
+            }
+            if full
+                && viewport.has_row(top + PROGRESS_BAR_ROW)
+                && let Some(width) = viewport.bar_width()
+            {
+                crossterm::queue!(
+                    writer,
+                    cursor::MoveTo(0, top + PROGRESS_BAR_ROW),
+                    style::Print(ProgressBar {
+                        ratio: elapsed_ratio(state.initial_duration, state.duration),
+                        width,
+                        color: options.bar_colors.map(|colors| {
+                            colors.color(remaining_ratio(state.initial_duration, state.duration))
+                        }),
+                    }),
+                )?;
+            }
+            if let Some(remaining) = state.milestones.banner(options.clock.now())
+                && viewport.has_row(top + MILESTONE_ROW)
+            {
+                crossterm::queue!(
+                    writer,
+                    cursor::MoveTo(0, top + MILESTONE_ROW),
+                    style::SetAttribute(style::Attribute::Reverse),
+                    style::Print(format_args!(" {} left ", DurationDisplay(remaining))),
+                    style::SetAttribute(style::Attribute::Reset),
+                )?;
+            }
+            if let Some(banner) = state.mute_banner(options.clock.now())
+                && viewport.has_row(top + MUTE_ROW)
+            {
+                crossterm::queue!(
+                    writer,
+                    cursor::MoveTo(0, top + MUTE_ROW),
+                    style::Print(banner),
+                )?;
+            }
+            if let Some(lap) = state.laps.last()
+                && viewport.has_row(top + LAP_ROW)
+            {
+                crossterm::queue!(writer, cursor::MoveTo(0, top + LAP_ROW), style::Print(lap),)?;
+            }
+            if let Some(edit) = &state.edit
+                && viewport.has_row(top + EDIT_ROW)
+            {
+                crossterm::queue!(
+                    writer,
+                    cursor::MoveTo(0, top + EDIT_ROW),
+                    style::Print(format_args!(
+                        "New remaining time (Esc to cancel): {}_",
+                        edit.input
+                    )),
+                )?;
+                if let Some(error) = &edit.error
+                    && viewport.has_row(top + EDIT_ERROR_ROW)
+                {
+                    crossterm::queue!(
+                        writer,
+                        cursor::MoveTo(0, top + EDIT_ERROR_ROW),
+                        style::SetForegroundColor(style::Color::Red),
+                        style::Print(error),
+                        style::ResetColor,
+                    )?;
+                }
+            }
+            queue_title(writer, state, options)?;
+            queue_taskbar_progress(writer, state, options)?;
+            crossterm::execute!(writer, terminal::EndSynchronizedUpdate)
+        })
+}
+
+/// Redraws the time on the current line, for `--inline`.
+fn render_inline(
+    writer: &mut impl io::Write,
+    state: &TimerState,
+    options: &TimerOptions,
+) -> io::Result<()> {
+    crossterm::queue!(
+        writer,
+        terminal::BeginSynchronizedUpdate,
+        cursor::MoveToColumn(0),
+        terminal::Clear(terminal::ClearType::CurrentLine),
+    )?;
+    if let Some(header) = &state.header {
+        crossterm::queue!(writer, style::Print(format_args!("{header}: ")))?;
+    }
+    queue_time_line(writer, state, options)?;
+    if state.is_paused() {
+        crossterm::queue!(writer, style::Print(" (paused)"))?;
+    }
+    if let Some(remaining) = state.milestones.banner(options.clock.now()) {
+        crossterm::queue!(
+            writer,
+            style::Print(' '),
+            style::SetAttribute(style::Attribute::Reverse),
+            style::Print(format_args!(" {} left ", DurationDisplay(remaining))),
+            style::SetAttribute(style::Attribute::Reset),
+        )?;
+    }
+    if let Some(lap) = state.laps.last() {
+        crossterm::queue!(writer, style::Print(format_args!(" ({lap})")))?;
+    }
+    if let Some(banner) = state.mute_banner(options.clock.now()) {
+        crossterm::queue!(writer, style::Print(format_args!(" ({banner})")))?;
+    }
+    if let Some(edit) = &state.edit {
+        crossterm::queue!(
+            writer,
+            style::Print(format_args!(" New remaining time: {}_", edit.input)),
+        )?;
+        if let Some(error) = &edit.error {
+            crossterm::queue!(
+                writer,
+                style::SetForegroundColor(style::Color::Red),
+                style::Print(format_args!(" {error}")),
+                style::ResetColor,
+            )?;
+        }
+    }
+    queue_title(writer, state, options)?;
+    queue_taskbar_progress(writer, state, options)?;
+    crossterm::execute!(writer, terminal::EndSynchronizedUpdate)
+}
+
+/// Prints the [`time_line`] in its [`time_color`], blinking with `--blink`.
+fn queue_time_line(
+    writer: &mut impl io::Write,
+    state: &TimerState,
+    options: &TimerOptions,
+) -> io::Result<()> {
+    let color = time_color(state, options);
+    // Counting whole periods of the running time, which stands still while paused
+    let ticks = state.elapsed().as_nanos() / options.tick_period.as_nanos();
+    // Reverse the video on every other tick
+    let blink = options.blink && is_warning(state, options) && !state.is_paused() && ticks % 2 == 1;
+    if blink {
+        crossterm::queue!(writer, style::SetAttribute(style::Attribute::Reverse))?;
+    }
+    crossterm::queue!(
+        writer,
+        style::SetForegroundColor(color),
+        style::Print(time_line(state, options)),
+        style::ResetColor,
+    )?;
+    if blink {
+        // Don't let the reverse video bleed into whatever is printed next
+        crossterm::queue!(writer, style::SetAttribute(style::Attribute::NoReverse))?;
+    }
+    if options.spinner {
+        crossterm::queue!(
+            writer,
+            style::Print(' '),
+            style::Print(SPINNER[(ticks % SPINNER.len() as u128) as usize]),
+        )?;
+    }
+    Ok(())
+}
+
+/// The frames of the spinner next to the time, one per tick.
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// The main line of the running display, like "Remaining time: 1m 30s (50%)".
+fn time_line(state: &TimerState, options: &TimerOptions) -> String {
+    let duration_style = duration_style(state, options);
+    let line = match (&options.format, options.mode) {
+        (Some(format), _) => format.render(
+            state.initial_duration,
+            state.duration,
+            state.has_end,
+            duration_style,
+            options.clock_format,
+        ),
+        (None, Mode::CountDown) if options.hidden => "Remaining time: ???".to_owned(),
+        (None, Mode::CountDown) if state.detail == Detail::Time => format!(
+            "Remaining time: {}",
+            duration_style.remaining(state.duration)
+        ),
+        (None, Mode::CountDown) => format!(
+            "Remaining time: {} ({:.0}%)",
+            duration_style.remaining(state.duration),
+            100.0 * remaining_ratio(state.initial_duration, state.duration),
+        ),
+        (None, Mode::CountUp) if state.has_end && state.detail != Detail::Time => format!(
+            "Elapsed time: {} ({:.0}%)",
+            duration_style.display(state.elapsed()),
+            100.0 * elapsed_ratio(state.initial_duration, state.duration),
+        ),
+        (None, Mode::CountUp) => {
+            format!("Elapsed time: {}", duration_style.display(state.elapsed()))
+        }
+    };
+    match (&options.format, state.repetition - 1) {
+        (None, 1) if options.every => format!("{line}, 1 interval done"),
+        (None, done) if options.every => format!("{line}, {done} intervals done"),
+        _ => line,
+    }
+}
+
+/// The style of the durations on the running display,
+/// leaving out the seconds while more than `--coarse` remains.
+fn duration_style(state: &TimerState, options: &TimerOptions) -> DurationStyle {
+    DurationStyle {
+        minutes_only: state.has_end && options.coarse.is_some_and(|coarse| state.duration > coarse),
+        ..options.duration_style
+    }
+}
+
+/// Red once no more than `--warn-at` remains, and the `--color`, if any, until then.
+fn time_color(state: &TimerState, options: &TimerOptions) -> style::Color {
+    if is_warning(state, options) {
+        style::Color::Red
+    } else {
+        options.color.unwrap_or(style::Color::Reset)
+    }
+}
+
+/// Whether no more than `--warn-at` remains.
+fn is_warning(state: &TimerState, options: &TimerOptions) -> bool {
+    state.has_end && !options.hidden && state.duration <= options.warn_at
+}
+
+/// Shows the time in the terminal title with `--set-title`.
+fn queue_title(
+    writer: &mut impl io::Write,
+    state: &TimerState,
+    options: &TimerOptions,
+) -> io::Result<()> {
+    if !options.set_title {
+        return Ok(());
+    }
+    let time = match options.mode {
+        Mode::CountDown if options.hidden => "???".to_owned(),
+        Mode::CountDown => duration_style(state, options)
+            .remaining(state.duration)
+            .to_string(),
+        Mode::CountUp => duration_style(state, options)
+            .display(state.elapsed())
+            .to_string(),
+    };
+    crossterm::queue!(
+        writer,
+        terminal::SetTitle(match &state.label {
+            Some(label) => format!("{label}: {time}"),
+            None => time,
+        }),
+    )
+}
+
+/// Shows the progress on the taskbar with `--taskbar-progress`, unless there's no end to
+/// measure it against or the duration is hidden.
+fn queue_taskbar_progress(
+    writer: &mut impl io::Write,
+    state: &TimerState,
+    options: &TimerOptions,
+) -> io::Result<()> {
+    if !options.taskbar_progress || !state.has_end || options.hidden {
+        return Ok(());
+    }
+    crossterm::queue!(
+        writer,
+        TaskbarProgress::new(
+            elapsed_ratio(state.initial_duration, state.duration),
+            state.is_paused(),
+        ),
+    )
+}
+
+/// Runs the timer without raw mode or the alternate screen, printing the time to the `--output`
+/// stream as a plain line every `--accessible` interval or second, e.g., when it's redirected to
+/// a file.
+///
+/// Keys are only read from stdin with `--stdin-commands`, otherwise the timer can only be
+/// stopped with Ctrl+C or SIGTERM.
+async fn run_lines(
+    duration: Option<Duration>,
+    options: &TimerOptions,
+    segment: Segment<'_>,
+    commands: &mut StdinCommands,
+    terminate: &mut TerminateSignal,
+) -> Result<Outcome, Error> {
+    if segment.number == 1
+        && let Some(delay) = options.delay
+        && let Outcome::Interrupted(_) =
+            wait_countdown_lines(delay, "Starting", options, commands, terminate).await?
+    {
+        if !options.no_message {
+            print_output(
+                options,
+                format_args!(
+                    "{} cancelled before it started.\n",
+                    Named::timer(&segment.label.map(str::to_owned))
+                ),
+            )?;
+        }
+        return Ok(Outcome::Interrupted(Duration::ZERO));
+    }
+    let mut state = TimerState::new(duration, options.clock.now());
+    state.label = segment.label.map(str::to_owned);
+    state.header = segment.header();
+    if state.has_end {
+        state.milestones = Milestones::new(&options.announce, state.initial_duration);
+    }
+    let started_at = Local::now();
+    let mut elapsed = Duration::ZERO;
+
+    let mut interval = tokio::time::interval(options.clock.real_duration(options.line_interval));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    // Without commands from stdin, nothing could resume the timer
+    if options.pause_on_start && options.stdin_commands && segment.number == 1 {
+        state.pause(state.started_at);
+        print_output(options, format_args!("{}\n", options.keys.paused_hint()))?;
+    }
+
+    loop {
+        // Wake up for the end and the milestones too, which may fall between the lines
+        let wake_at = if state.is_paused() {
+            None
+        } else {
+            state.deadline(options.clock.now()).and_then(|deadline| {
+                deadline.checked_sub(state.milestones.upcoming().unwrap_or_default())
+            })
+        };
+        let print_time = tokio::select! {
+            res = tokio::signal::ctrl_c() => {
+                res.map_err(|err| {
+                    system_with_internal(
+                        "Failed to listen for Ctrl+C",
+                        "Try notifying the developer",
+                        err,
+                    )
+                })?;
+                return stop_lines(started_at, &mut state, elapsed, options);
+            }
+            () = terminate.recv() => {
+                return stop_lines(started_at, &mut state, elapsed, options);
+            }
+            command = commands.next() => {
+                let key = command.map_err(|err| {
+                    system_with_internal(
+                        "Failed to read the commands from stdin",
+                        "Make sure stdin is still open",
+                        err,
+                    )
+                })?;
+                let now = options.clock.now();
+                if key == options.keys.quit {
+                    return stop_lines(started_at, &mut state, elapsed, options);
+                } else if key == options.keys.pause && state.is_paused() {
+                    state.resume(now);
+                } else if key == options.keys.pause {
+                    state.pause(now);
+                    print_output(options, format_args!("{}\n", options.keys.paused_hint()))?;
+                } else if key == options.keys.restart {
+                    state.reset(now);
+                    state.laps.clear();
+                } else if key == options.keys.reset {
+                    if segment.number > 1 {
+                        return Ok(Outcome::Reset);
+                    }
+                    state.reset(now);
+                    state.laps.clear();
+                    state.repetition = 1;
+                    elapsed = Duration::ZERO;
+                } else if key == 'l' && options.mode == Mode::CountUp {
+                    state.update(now);
+                    print_output(
+                        options,
+                        format_args!("{}\n", state.laps.record(state.elapsed())),
+                    )?;
+                }
+                continue;
+            }
+            _ = interval.tick() => true,
+            () = async {
+                match wake_at {
+                    Some(wake_at) => tokio::time::sleep_until(options.clock.real(wake_at)).await,
+                    None => core::future::pending().await,
+                }
+            } => false,
+        };
+
+        let now = options.clock.now();
+        state.update(now);
+        if state.is_paused() {
+            continue;
+        }
+        if state.duration.is_zero() {
+            if options.repeat.is_last(state.repetition) {
+                break;
+            }
+            state.repetition += 1;
+            elapsed += state.elapsed();
+            state.reset(now);
+            match options.repeat {
+                _ if options.every => print_output(
+                    options,
+                    format_args!("Starting interval {}\n", state.repetition),
+                )?,
+                Repeat::Times(times) => print_output(
+                    options,
+                    format_args!("Starting repetition {} of {times}\n", state.repetition),
+                )?,
+                Repeat::Infinite => print_output(
+                    options,
+                    format_args!("Starting repetition {}\n", state.repetition),
+                )?,
+            }
+            options.broadcast(StatusEvent::Repetition {
+                repetition: state.repetition,
+            });
+            if options.every {
+                remind(options, state.initial_duration, false)?;
+            }
+            continue;
+        }
+        if let Some(remaining) = state.milestones.reach(state.duration, now) {
+            print_output(
+                options,
+                format_args!("{} left\n", DurationDisplay(remaining)),
+            )?;
+        }
+        if print_time {
+            options.broadcast(StatusEvent::tick(&state));
+            match &state.header {
+                Some(header) => print_output(
+                    options,
+                    format_args!("{header}: {}\n", time_line(&state, options)),
+                )?,
+                None => print_output(options, format_args!("{}\n", time_line(&state, options)))?,
+            }
+        }
+    }
+
+    let elapsed = elapsed + state.elapsed();
+    options.broadcast(StatusEvent::Finished {
+        elapsed_ms: status::millis(elapsed),
+    });
+    if segment.is_last() {
+        if !options.no_message {
+            print_output(
+                options,
+                format_args!(
+                    "{} finished after {}!\n",
+                    Named::timer(&state.label),
+                    finished_time(elapsed, options)
+                ),
+            )?;
+        }
+        print_output(options, format_args!("{}", state.laps))?;
+    }
+
+    if options.log {
+        log_history(started_at, &state, elapsed, HistoryOutcome::Completed);
+    }
+
+    Ok(Outcome::Completed(elapsed))
+}
+
+/// Reports a timer stopped by the user in the line-based output.
+fn stop_lines(
+    started_at: DateTime<Local>,
+    state: &mut TimerState,
+    elapsed: Duration,
+    options: &TimerOptions,
+) -> Result<Outcome, Error> {
+    let now = options.clock.now();
+    state.update(now);
+    let running_time = state.running_time(now);
+    match options.mode {
+        _ if options.no_message => {}
+        Mode::CountDown => print_output(
+            options,
+            format_args!(
+                "{} stopped by user at {}, after {}.\n",
+                Named::timer(&state.label),
+                DurationDisplay::remaining(state.duration, false),
+                DurationDisplay(running_time),
+            ),
+        )?,
+        Mode::CountUp => print_output(
+            options,
+            format_args!(
+                "{} stopped by user after {}.\n",
+                Named::stopwatch(&state.label),
+                DurationDisplay(running_time),
+            ),
+        )?,
+    }
+    print_output(options, format_args!("{}", state.laps))?;
+    record_stop(started_at, state, elapsed + running_time, options);
+    Ok(Outcome::Interrupted(elapsed + running_time))
+}
+
+/// Runs the timer without the terminal UI, printing a [`StatusEvent`] to stdout on every tick.
+///
+/// Keys aren't read in this mode, so the timer can only be stopped by a signal, which prints a
+/// last "stopped" event on SIGTERM.
+async fn run_json(
+    duration: Option<Duration>,
+    options: &TimerOptions,
+    terminate: &mut TerminateSignal,
+) -> Result<Outcome, Error> {
+    let mut state = TimerState::new(duration, options.clock.now());
+    let mut elapsed = Duration::ZERO;
+
+    let tick_period = Duration::from_secs(1);
+    let mut interval = tokio::time::interval_at(
+        options.clock.real(state.started_at),
+        options.clock.real_duration(tick_period),
+    );
+
+    let mut stdout = io::stdout();
+    let mut repetition = 1;
+    let write_error = |err| {
+        system_with_internal(
+            "Failed to write the status to stdout",
+            "Make sure stdout is still open",
+            err,
+        )
+    };
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            () = terminate.recv() => {
+                state.update(options.clock.now());
+                let elapsed = elapsed + state.elapsed();
+                StatusEvent::Stopped {
+                    elapsed_ms: status::millis(elapsed),
+                }
+                .write_line(&mut stdout)
+                .map_err(write_error)?;
+                return Ok(Outcome::Interrupted(elapsed));
+            }
+        }
+        state.update(options.clock.now());
+        if state.duration.is_zero() {
+            if options.repeat.is_last(repetition) {
+                break;
+            }
+            repetition += 1;
+            elapsed += state.elapsed();
+            state.reset(options.clock.now());
+            StatusEvent::Repetition { repetition }
+                .write_line(&mut stdout)
+                .map_err(write_error)?;
+            continue;
+        }
+        StatusEvent::tick(&state)
+            .write_line(&mut stdout)
+            .map_err(write_error)?;
+    }
+
+    let elapsed = elapsed + state.elapsed();
+    StatusEvent::Finished {
+        elapsed_ms: status::millis(elapsed),
+    }
+    .write_line(&mut stdout)
+    .map_err(write_error)?;
+
+    Ok(Outcome::Completed(elapsed))
+}
+
+/// Waits for the timer, including all of its repetitions, to run out without any output.
+///
+/// A timer that never runs out waits until it's interrupted with Ctrl+C or SIGTERM.
+async fn run_quiet(
+    duration: Option<Duration>,
+    options: &TimerOptions,
+    terminate: &mut TerminateSignal,
+) -> Result<Outcome, Error> {
+    let total = duration.and_then(|duration| match options.repeat {
+        Repeat::Times(times) => duration.checked_mul(times.max(1)),
+        Repeat::Infinite => None,
+    });
+
+    let started_at = options.clock.now();
+    tokio::select! {
+        _ = async {
+            match total {
+                Some(total) => tokio::time::sleep(options.clock.real_duration(total)).await,
+                None => core::future::pending().await,
+            }
+        } => {}
+        res = tokio::signal::ctrl_c() => {
+            res.map_err(|err| {
+                system_with_internal(
+                    "Failed to listen for Ctrl+C",
+                    "Try notifying the developer",
+                    err,
+                )
+            })?;
+            return Ok(Outcome::Interrupted(
+                options.clock.now().saturating_duration_since(started_at),
+            ));
+        }
+        () = terminate.recv() => {
+            return Ok(Outcome::Interrupted(
+                options.clock.now().saturating_duration_since(started_at),
+            ));
+        }
+    }
+
+    Ok(Outcome::Completed(total.unwrap_or_default()))
+}
+
+/// Runs the actions for a timer that ran out: the bell, notification, sound and command.
+///
+/// Only the command runs if the timer was `muted` with the mute key.
+#[cfg_attr(not(feature = "sound"), expect(unused_variables))]
+async fn finish(
+    writer: &mut Output,
+    elapsed: Duration,
+    options: &TimerOptions,
+    muted: bool,
+    terminate: &mut TerminateSignal,
+) -> Result<(), Error> {
+    #[cfg(feature = "notify")]
+    if options.notify && !muted {
+        // A missing notification daemon shouldn't turn a finished timer into a failure
+        if let Err(e) = send_notification(&options.label, elapsed) {
+            eprintln!("{e}");
+        }
+    }
+    // A bell would only end up as a stray control character in a redirected output
+    let bell_count = if writer.is_tty() && !muted {
+        options.bell_count
+    } else {
+        0
+    };
+    ring_bell(writer, bell_count).await.map_err(|err| {
+        system_with_internal(
+            "Failed to write to the terminal",
+            "Try notifying the developer",
+            err,
+        )
+    })?;
 
-use clap::Parser;
-use crossterm::{
-    cursor,
-    event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
-    style, terminal,
-};
-use futures_util::{FutureExt, TryStreamExt};
-use human_errors::{Error, system_with_internal, user, user_with_cause, user_with_internal};
+    #[cfg(feature = "sound")]
+    if let Some(path) = &options.sound
+        && !muted
+    {
+        // Like the notification, a sound that can't be played shouldn't fail a finished timer
+        match sound::Sound::play(path, options.sound_loop) {
+            Ok(sound) if options.sound_loop => {
+                wait_for_key(writer, "Press any key to stop the sound.", terminate).await?;
+                drop(sound);
+            }
+            Ok(sound) => sound.finished().await,
+            Err(e) => eprintln!("{e}"),
+        }
+    }
 
-fn main() -> ExitCode {
-    let Args {
-        duration: ColonSeparatedDuration(duration),
-    } = Args::parse();
+    run_exec(options, elapsed, "completed")
+}
 
-    let rt = match tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()
+/// Reports the end of an `--every` interval with the notification and the command,
+/// the announcement of the next interval already ringing the bell.
+///
+/// Only the command runs if the timer was `muted` with the mute key.
+#[cfg_attr(not(feature = "notify"), expect(unused_variables))]
+fn remind(options: &TimerOptions, interval: Duration, muted: bool) -> Result<(), Error> {
+    #[cfg(feature = "notify")]
+    if options.notify
+        && !muted
+        && let Err(e) = send_notification(&options.label, interval)
+    {
+        eprintln!("{e}");
+    }
+    run_exec(options, interval, "interval")
+}
+
+/// Runs the `--exec` command, if any, telling it how the timer ended through its environment.
+///
+/// `outcome` is "completed", "interval" for the end of an `--every` interval, or "stopped".
+fn run_exec(options: &TimerOptions, elapsed: Duration, outcome: &str) -> Result<(), Error> {
+    match &options.exec {
+        Some(command) => spawn_shell_command(command, &exec_env(&options.label, elapsed, outcome)),
+        None => Ok(()),
+    }
+}
+
+/// The environment variables given to the `--exec` command: `TIMER_OUTCOME`,
+/// `TIMER_ELAPSED` in whole seconds, and `TIMER_LABEL`, empty without a label.
+fn exec_env(
+    label: &Option<String>,
+    elapsed: Duration,
+    outcome: &str,
+) -> [(&'static str, String); 3] {
+    [
+        ("TIMER_OUTCOME", outcome.to_owned()),
+        ("TIMER_ELAPSED", elapsed.as_secs().to_string()),
+        ("TIMER_LABEL", label.clone().unwrap_or_default()),
+    ]
+}
+
+/// Shows `prompt` and waits until any key is pressed, after the terminal has been restored.
+async fn wait_for_key(
+    writer: &mut Output,
+    prompt: &str,
+    terminate: &mut TerminateSignal,
+) -> Result<(), Error> {
+    read_key(writer, prompt, terminate, |_| Some(()))
+        .await
+        .map(drop)
+}
+
+/// Asks whether to start a timer of `duration`, which is longer than `threshold` of
+/// `--warn-long`, returning whether `y` was pressed rather than any other key.
+async fn confirm_long_duration(
+    writer: &mut Output,
+    duration: Duration,
+    threshold: Duration,
+    terminate: &mut TerminateSignal,
+) -> Result<bool, Error> {
+    let prompt = format!(
+        "This timer of {} is longer than {}, continue? [y/N]",
+        DurationDisplay(duration),
+        DurationDisplay(threshold),
+    );
+    let confirmed = read_key(writer, &prompt, terminate, |key| {
+        Some(matches!(key.code, KeyCode::Char('y' | 'Y')))
+    })
+    .await?;
+    Ok(confirmed == Some(true))
+}
+
+/// Asks whether to snooze a finished timer, returning whether the snooze key was pressed
+/// rather than the quit key, `Esc` or `Ctrl`+`C`.
+async fn wait_for_snooze(
+    writer: &mut Output,
+    snooze: Duration,
+    options: &TimerOptions,
+    terminate: &mut TerminateSignal,
+) -> Result<bool, Error> {
+    let prompt = format!(
+        "Finished, press {} to snooze for {} or {} to quit.",
+        KeyDisplay(SNOOZE_KEY),
+        DurationDisplay(snooze),
+        KeyDisplay(options.keys.quit),
+    );
+    let snoozed = read_key(writer, &prompt, terminate, |key| {
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('c'), KeyModifiers::CONTROL) => Some(false),
+            (KeyCode::Char(SNOOZE_KEY), _) => Some(true),
+            (KeyCode::Char(key), _) if key == options.keys.quit => Some(false),
+            (KeyCode::Esc, _) => Some(false),
+            _ => None,
+        }
+    })
+    .await?;
+    Ok(snoozed == Some(true))
+}
+
+/// The key that silences the bell, notification and sound of a running timer, or turns them
+/// back on.
+const MUTE_KEY: char = 'm';
+
+/// The key that snoozes a finished timer with `--snooze`.
+const SNOOZE_KEY: char = 's';
+
+/// Shows `prompt` and waits until a key that `accept` maps to a value is pressed, returning
+/// that value, or `None` if there are no more events.
+///
+/// SIGTERM arrives as Ctrl+C, like in [`next_event`].
+async fn read_key<T>(
+    writer: &mut Output,
+    prompt: &str,
+    terminate: &mut TerminateSignal,
+    mut accept: impl FnMut(KeyEvent) -> Option<T>,
+) -> Result<Option<T>, Error> {
+    use std::io::Write;
+
+    let mut event_stream = EventStream::new();
+    writeln!(writer, "{prompt}")
+        .and_then(|_| crossterm::terminal::enable_raw_mode())
         .map_err(|err| {
             system_with_internal(
-                "Failed to build the runtime",
+                "Failed to write to the terminal",
                 "Try notifying the developer",
                 err,
             )
-        }) {
-        Ok(runtime) => runtime,
-        Err(e) => {
-            eprintln!("{e}");
-            return ExitCode::FAILURE;
+        })?;
+    let result = loop {
+        let event = tokio::select! {
+            event = event_stream.try_next() => event,
+            () = terminate.recv() => Ok(Some(Event::Key(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL,
+            )))),
+        };
+        match event {
+            Ok(Some(Event::Key(
+                key @ KeyEvent {
+                    kind: KeyEventKind::Press,
+                    ..
+                },
+            ))) => match accept(key) {
+                Some(value) => break Ok(Some(value)),
+                None => continue,
+            },
+            Ok(None) => break Ok(None),
+            Ok(Some(_)) => continue,
+            Err(err) => {
+                break Err(system_with_internal(
+                    "Failed to read events",
+                    "Try notifying the developer",
+                    err,
+                ));
+            }
         }
     };
+    crossterm::terminal::disable_raw_mode().map_err(|err| {
+        system_with_internal(
+            "Failed to restore the terminal",
+            "Try notifying the developer",
+            err,
+        )
+    })?;
+    result
+}
+
+/// Runs `command` through the platform shell with the extra environment variables `env`,
+/// and waits for it to finish.
+fn spawn_shell_command(command: &str, env: &[(&str, String)]) -> Result<(), Error> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = std::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = std::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .status()
+        .map(drop)
+        .map_err(|err| {
+            system_with_internal(
+                &format!("Failed to run the command \"{command}\""),
+                "Make sure the shell is available and the command is valid",
+                err,
+            )
+        })
+}
+
+#[cfg(feature = "notify")]
+fn send_notification(label: &Option<String>, elapsed: Duration) -> Result<(), Error> {
+    notify_rust::Notification::new()
+        .summary(&format!("{} finished!", Named::timer(label)))
+        .body(&format!("Finished after {}", DurationDisplay(elapsed)))
+        .show()
+        .map(drop)
+        .map_err(|err| {
+            system_with_internal(
+                "Failed to show the desktop notification",
+                "Make sure a notification service is running",
+                err,
+            )
+        })
+}
+
+/// Flashes the screen `--flash-count` times by filling it with white,
+/// redrawing the display in between.
+async fn flash(writer: &mut Output, state: &TimerState, options: &TimerOptions) -> io::Result<()> {
+    let clear = if options.inline {
+        terminal::ClearType::CurrentLine
+    } else {
+        terminal::ClearType::All
+    };
+    for _ in 0..options.flash_count {
+        let res = crossterm::execute!(
+            writer,
+            style::SetBackgroundColor(style::Color::White),
+            terminal::Clear(clear),
+        );
+        tokio::time::sleep(options.flash_interval).await;
+        // Reset the color even if filling the screen failed halfway
+        crossterm::execute!(writer, style::ResetColor)?;
+        res.and_then(|_| render(writer, state, options))?;
+        tokio::time::sleep(options.flash_interval).await;
+    }
+    Ok(())
+}
 
-    let result = rt.block_on(run_timer(duration));
+/// Rings the terminal bell `count` times, pausing between rings so they don't blend together.
+async fn ring_bell(writer: &mut Output, count: u32) -> io::Result<()> {
+    use std::io::Write;
 
-    if let Err(e) = result {
-        eprintln!("{e}");
-        return ExitCode::FAILURE;
+    for i in 0..count {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+        }
+        writer.write_all(b"\x07")?;
+        writer.flush()?;
     }
+    Ok(())
+}
 
-    ExitCode::SUCCESS
+/// Settings of a timer that don't change while it runs.
+struct TimerOptions {
+    mode: Mode,
+    repeat: Repeat,
+    /// Whether the repetitions are the intervals of `--every`, each finishing like a timer.
+    every: bool,
+    warn_at: Duration,
+    /// Whether to blink the time while it's red.
+    blink: bool,
+    /// Whether to turn a spinner next to the time on every tick.
+    spinner: bool,
+    /// Whether to hide how much time is left, for `--random`.
+    hidden: bool,
+    format: Option<DisplayFormat>,
+    clock_format: ClockFormat,
+    /// How often to redraw the display.
+    tick_period: Duration,
+    clock: Clock,
+    duration_style: DurationStyle,
+    /// Show only the minutes while more than this remains.
+    coarse: Option<Duration>,
+    /// How to color the progress bar, if at all.
+    bar_colors: Option<BarColors>,
+    /// The color of the running time before it turns red.
+    color: Option<style::Color>,
+    keys: KeyBindings,
+    /// How many times to ring the bell on completion.
+    bell_count: u32,
+    /// How many times to flash the screen on completion.
+    flash_count: u32,
+    /// How long each flash lasts, and the pause between flashes.
+    flash_interval: Duration,
+    /// Remaining times to announce.
+    announce: Box<[Duration]>,
+    #[cfg(feature = "notify")]
+    notify: bool,
+    /// Shell command to run on completion.
+    exec: Option<String>,
+    /// Whether to also run the command when the timer is stopped.
+    exec_on_stop: bool,
+    /// Sound file to play on completion.
+    #[cfg(feature = "sound")]
+    sound: Option<std::path::PathBuf>,
+    #[cfg(feature = "sound")]
+    sound_loop: bool,
+    /// File to keep the current time in.
+    status_file: Option<std::path::PathBuf>,
+    /// Socket to send the status to.
+    #[cfg(unix)]
+    socket: Option<status_socket::StatusSocket>,
+    /// Whether to show the current time in the terminal title.
+    set_title: bool,
+    /// Whether to show the progress on the taskbar.
+    taskbar_progress: bool,
+    label: Option<String>,
+    /// File to save a stopped timer to.
+    save_state: Option<std::path::PathBuf>,
+    /// Whether to append the timer to the history log.
+    log: bool,
+    /// Whether to wait for a key press before exiting once the timer finishes.
+    wait_on_finish: bool,
+    /// How long to count down again when snoozing a finished timer.
+    snooze: Option<Duration>,
+    /// Whether to leave out the finished and stopped messages.
+    no_message: bool,
+    /// Whether to clear the screen, or the inline line, on exit.
+    clear_on_exit: bool,
+    /// Whether the first timer starts paused.
+    pause_on_start: bool,
+    /// How long to wait before the first timer starts.
+    delay: Option<Duration>,
+    /// How long to wait between the steps, never zero.
+    gap: Option<Duration>,
+    /// Whether to draw on the current line instead of the alternate screen.
+    inline: bool,
+    /// The stream to draw on.
+    output: OutputStream,
+    /// Whether to read keys from stdin, one per line.
+    stdin_commands: bool,
+    /// Whether to print plain lines even on a terminal, for screen readers.
+    accessible: bool,
+    /// Whether to print plain lines even on a terminal, without raw mode.
+    no_raw_mode: bool,
+    /// How often to print a line when not drawing on a terminal.
+    line_interval: Duration,
 }
 
-#[derive(Parser)]
-#[command(version, about, long_about = None)]
-struct Args {
-    #[arg(
-        name = "[[[d:]h:]m:]s duration",
-        help = "Duration in the format \"[[[d:]h:]m:]s\" (e.g., \"1:2:3:4\" for 1 day, 2 hours, 3 minutes, and 4 seconds)"
-    )]
-    duration: ColonSeparatedDuration,
+impl TimerOptions {
+    /// Sends the event to the clients of `--socket`, if there's one.
+    fn broadcast(&self, event: StatusEvent) {
+        #[cfg(unix)]
+        if let Some(socket) = &self.socket {
+            socket.send(event);
+        }
+        #[cfg(not(unix))]
+        let _ = event;
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct ColonSeparatedDuration(Duration);
+/// The state of a running timer, shared between the tick and the event branches.
+struct TimerState {
+    /// Whether the timer runs out at all, which an uncapped stopwatch does not.
+    has_end: bool,
+    initial_duration: Duration,
+    /// The remaining time.
+    duration: Duration,
+    /// When the timer was started, or last reset.
+    started_at: Instant,
+    /// When the timer was paused, if it currently is.
+    paused_at: Option<Instant>,
+    /// The total time spent paused before `paused_at`.
+    paused_total: Duration,
+    paused_print: bool,
+    label: Option<String>,
+    /// A line shown above the running display.
+    header: Option<String>,
+    /// The 1-based repetition of the timer, or interval of `--every`, that is running.
+    repetition: u32,
+    milestones: Milestones,
+    detail: Detail,
+    laps: Laps,
+    /// The new remaining time being typed after pressing `e`.
+    edit: Option<Edit>,
+    /// Whether the bell, notification and sound are silenced.
+    muted: bool,
+    /// When the mute key was last pressed.
+    muted_toggled_at: Option<Instant>,
+}
 
-impl FromStr for ColonSeparatedDuration {
-    type Err = Error;
+/// A new remaining time being typed in.
+#[derive(Debug, Clone, Default)]
+struct Edit {
+    input: String,
+    /// Why the last input was rejected.
+    error: Option<String>,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        parse_duration(s).map(Self)
+impl TimerState {
+    fn new(duration: Option<Duration>, now: Instant) -> Self {
+        let initial_duration = duration.unwrap_or(Duration::MAX);
+        Self {
+            has_end: duration.is_some(),
+            initial_duration,
+            duration: initial_duration,
+            started_at: now,
+            paused_at: None,
+            paused_total: Duration::ZERO,
+            paused_print: true,
+            label: None,
+            header: None,
+            repetition: 1,
+            milestones: Milestones::default(),
+            detail: Detail::default(),
+            laps: Laps::default(),
+            edit: None,
+            muted: false,
+            muted_toggled_at: None,
+        }
+    }
+
+    /// Silences the bell, notification and sound, or turns them back on.
+    fn toggle_mute(&mut self, now: Instant) {
+        self.muted = !self.muted;
+        self.muted_toggled_at = Some(now);
+    }
+
+    /// "Muted" or "Unmuted" for a while after the mute key was pressed.
+    fn mute_banner(&self, now: Instant) -> Option<&'static str> {
+        let toggled_at = self.muted_toggled_at?;
+        if now.saturating_duration_since(toggled_at) >= MUTE_BANNER_TIME {
+            return None;
+        }
+        Some(if self.muted { "Muted" } else { "Unmuted" })
+    }
+
+    /// The row the running display starts at, below the header if there is one.
+    fn top(&self) -> u16 {
+        u16::from(self.header.is_some())
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    fn pause(&mut self, now: Instant) {
+        self.paused_at.get_or_insert(now);
+    }
+
+    fn resume(&mut self, now: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_total += now.saturating_duration_since(paused_at);
+        }
+    }
+
+    /// Restarts the timer from its full duration, unpaused.
+    fn reset(&mut self, now: Instant) {
+        self.duration = self.initial_duration;
+        self.started_at = now;
+        self.paused_at = None;
+        self.paused_total = Duration::ZERO;
+        self.milestones.rearm(self.initial_duration);
+    }
+
+    /// The total time spent paused, including the current pause.
+    fn paused_time(&self, now: Instant) -> Duration {
+        self.paused_total
+            + self.paused_at.map_or(Duration::ZERO, |paused_at| {
+                now.saturating_duration_since(paused_at)
+            })
+    }
+
+    /// The instant the timer runs out at, pushed back by the time spent paused.
+    ///
+    /// `None` if the timer has no end.
+    fn deadline(&self, now: Instant) -> Option<Instant> {
+        if !self.has_end {
+            return None;
+        }
+        self.started_at
+            .checked_add(self.paused_time(now))?
+            .checked_add(self.initial_duration)
+    }
+
+    /// Recomputes the remaining time from the deadline, so that slow ticks don't make it drift.
+    fn update(&mut self, now: Instant) {
+        self.duration = match self.deadline(now) {
+            Some(deadline) => deadline.saturating_duration_since(now),
+            None => self.initial_duration.saturating_sub(self.running_time(now)),
+        };
+    }
+
+    /// The wall-clock time the timer has been running for, excluding pauses.
+    fn running_time(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started_at)
+            .saturating_sub(self.paused_time(now))
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.initial_duration.saturating_sub(self.duration)
+    }
+
+    /// Extends the remaining time, along with the total so that the elapsed time stays the same.
+    fn add_time(&mut self, amount: Duration) {
+        self.initial_duration = self.initial_duration.saturating_add(amount);
+        self.duration = self.duration.saturating_add(amount);
+    }
+
+    /// Replaces the remaining time, keeping the elapsed time the same.
+    fn set_remaining(&mut self, remaining: Duration) {
+        if remaining >= self.duration {
+            self.add_time(remaining - self.duration);
+        } else {
+            self.subtract_time(self.duration - remaining);
+        }
+    }
+
+    /// Shortens the remaining time, along with the total, never going below zero.
+    fn subtract_time(&mut self, amount: Duration) {
+        let amount = amount.min(self.duration);
+        self.initial_duration -= amount;
+        self.duration -= amount;
     }
 }
 
-async fn run_timer(mut duration: Duration) -> Result<(), Error> {
-    let initial_duration = duration;
+/// How long the banner of the mute key stays on the screen.
+const MUTE_BANNER_TIME: Duration = Duration::from_secs(3);
 
-    let tick_period = Duration::from_secs(1);
-    let mut interval = tokio::time::interval(tick_period);
+/// How long a milestone banner stays on the screen.
+const MILESTONE_BANNER_TIME: Duration = Duration::from_secs(3);
 
-    let mut writer = std::io::stderr();
-    crossterm::execute!(
-        &mut writer,
-        terminal::EnterAlternateScreen,
-        cursor::Hide,
-        cursor::MoveTo(0, 0)
-    )
-    .and_then(|_| crossterm::terminal::enable_raw_mode())
-    .map_err(|err| {
-        system_with_internal(
-            "Failed to enter alternate screen",
-            "Try notifying the developer",
-            err,
-        )
-    })?;
+/// The `--announce` remaining times of a timer, each announced once as the timer reaches it.
+#[derive(Debug, Clone, Default)]
+struct Milestones {
+    /// In descending order.
+    remaining: Box<[Duration]>,
+    /// The index of the next milestone to announce.
+    next: usize,
+    /// The last announced milestone, and when it was announced.
+    shown: Option<(Duration, Instant)>,
+}
 
-    let mut event_stream = EventStream::new();
-    let mut paused = false;
-    let mut paused_print = true;
+impl Milestones {
+    fn new(remaining: &[Duration], initial_duration: Duration) -> Self {
+        let mut remaining = remaining.to_vec();
+        remaining.sort_unstable_by(|a, b| b.cmp(a));
+        remaining.dedup();
+        let mut milestones = Self {
+            remaining: remaining.into_boxed_slice(),
+            ..Self::default()
+        };
+        milestones.rearm(initial_duration);
+        milestones
+    }
 
-    loop {
-        let event = event_stream.try_next().fuse();
-        let tick = interval.tick().fuse();
+    /// Starts over for a timer with `duration` left, skipping the milestones it's already past.
+    fn rearm(&mut self, duration: Duration) {
+        self.next = self
+            .remaining
+            .iter()
+            .position(|&remaining| remaining < duration)
+            .unwrap_or(self.remaining.len());
+        self.shown = None;
+    }
 
-        tokio::select! {
-            maybe_event = event => match process_event_branch(
-                maybe_event,
-                &mut writer,
-                &mut paused,
-                &mut paused_print,
-                initial_duration,
-                duration
-            ) {
-                ControlFlow::Return(res) => return res,
-                ControlFlow::Break => break,
-                ControlFlow::Continue => continue,
-            },
-            _ = tick => {
-                if paused {
-                    crossterm::queue!(
-                        writer,
-                        terminal::BeginSynchronizedUpdate,
-                        cursor::MoveTo(0, 2),
-                        style::Print("Timer is paused. Press 'p' to resume or 'q' to quit."),
-                    )
-                    .and_then(|_| print_paused(&mut writer, &mut paused_print))
-                    .map_err(|err| {
-                        system_with_internal(
-                            "Failed to write to the terminal",
-                            "Try notifying the developer",
-                            err,
-                        )
-                    })?;
-                    continue;
-                }
-                if duration.is_zero() {
-                    break;
-                }
-                crossterm::execute!(
-                    writer,
-                    terminal::BeginSynchronizedUpdate,
-                    terminal::Clear(terminal::ClearType::All),
-                    cursor::MoveTo(0, 0),
-                    style::Print(format_args!("Remaining time: {}", DurationDisplay(duration))),
-                    terminal::EndSynchronizedUpdate,
-                )
-                .map_err(|err| {
-                    system_with_internal(
-                        "Failed to write to the terminal",
-                        "Try notifying the developer",
-                        err,
-                    )
-                })?;
-                duration -= tick_period;
-            }
+    /// Returns the milestone reached with `duration` left, if it wasn't announced yet.
+    ///
+    /// If several were reached since the last call, like after a slow tick, only the last one is
+    /// returned, and the others are skipped.
+    fn reach(&mut self, duration: Duration, now: Instant) -> Option<Duration> {
+        let mut reached = None;
+        while let Some(&remaining) = self.remaining.get(self.next)
+            && duration <= remaining
+        {
+            reached = Some(remaining);
+            self.next += 1;
+        }
+        if let Some(remaining) = reached {
+            self.shown = Some((remaining, now));
         }
+        reached
     }
 
-    crossterm::execute!(writer, cursor::Show, terminal::LeaveAlternateScreen)
-        .and_then(|_| {
-            use std::io::Write;
+    /// The next milestone to announce, if any.
+    fn upcoming(&self) -> Option<Duration> {
+        self.remaining.get(self.next).copied()
+    }
 
-            crossterm::terminal::disable_raw_mode()?;
-            writeln!(writer, "Timer finished!")
-        })
-        .map_err(|err| {
-            system_with_internal(
-                "Failed to clear the terminal",
-                "Try notifying the developer",
-                err,
-            )
-        })
+    /// The milestone to show a banner for at `now`, if one was announced recently.
+    fn banner(&self, now: Instant) -> Option<Duration> {
+        self.shown
+            .filter(|&(_, shown_at)| {
+                now.saturating_duration_since(shown_at) < MILESTONE_BANNER_TIME
+            })
+            .map(|(remaining, _)| remaining)
+    }
 }
 
 enum ControlFlow {
     Return(Result<(), Error>),
     Break,
+    /// Start the whole sequence over.
+    Reset,
     Continue,
 }
 
+/// How much the `+` and `-` keys change the remaining time by.
+const ADJUST_STEP: Duration = Duration::from_secs(60);
+
 #[inline]
 fn process_event_branch(
     maybe_event: io::Result<Option<Event>>,
-    writer: &mut io::Stderr,
-    paused: &mut bool,
-    paused_print: &mut bool,
-    initial_duration: Duration,
-    duration: Duration,
+    writer: &mut TerminalGuard<Output>,
+    state: &mut TimerState,
+    options: &TimerOptions,
 ) -> ControlFlow {
     match maybe_event {
         Ok(None) => ControlFlow::Break,
         Ok(Some(event)) => match event {
-            Event::Key(
-                KeyEvent {
-                    code: KeyCode::Char('q'),
-                    kind: KeyEventKind::Press,
-                    ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('c'),
-                    kind: KeyEventKind::Press,
-                    modifiers: KeyModifiers::CONTROL,
-                    ..
-                },
-            ) => ControlFlow::Return(
-                crossterm::execute!(writer, cursor::Show, terminal::LeaveAlternateScreen)
-                    .and_then(|_| {
-                        use std::io::Write;
+            Event::Key(KeyEvent {
+                code,
+                kind: KeyEventKind::Press,
+                modifiers,
+                ..
+            }) if state.edit.is_some()
+                && !(code == KeyCode::Char('c') && modifiers == KeyModifiers::CONTROL) =>
+            {
+                process_edit_key(code, writer, state, options)
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(key),
+                kind: KeyEventKind::Press,
+                modifiers,
+                ..
+            }) if key == options.keys.quit
+                || (key == 'c' && modifiers == KeyModifiers::CONTROL) =>
+            {
+                ControlFlow::Return(
+                    writer
+                        .restore()
+                        .and_then(|_| {
+                            use std::io::Write;
 
-                        crossterm::terminal::disable_raw_mode()?;
-                        writeln!(
-                            writer,
-                            "Timer stopped by user at {}, after {}.",
-                            DurationDisplay(duration),
-                            DurationDisplay(initial_duration - duration),
-                        )
-                    })
-                    .map_err(|err| {
-                        system_with_internal(
-                            "Failed to clear the terminal",
-                            "Try notifying the developer",
-                            err,
-                        )
-                    }),
-            ),
+                            if options.set_title {
+                                crossterm::execute!(
+                                    writer,
+                                    terminal::SetTitle(format_args!(
+                                        "{} stopped",
+                                        Named::timer(&state.label)
+                                    ))
+                                )?;
+                            }
+                            match options.mode {
+                                _ if options.no_message => Ok(()),
+                                Mode::CountDown => writeln!(
+                                    writer,
+                                    "{} stopped by user at {}, after {}.",
+                                    Named::timer(&state.label),
+                                    DurationDisplay::remaining(state.duration, false),
+                                    DurationDisplay(state.running_time(options.clock.now())),
+                                ),
+                                Mode::CountUp => writeln!(
+                                    writer,
+                                    "{} stopped by user after {}.",
+                                    Named::stopwatch(&state.label),
+                                    DurationDisplay(state.running_time(options.clock.now())),
+                                ),
+                            }?;
+                            write!(writer, "{}", state.laps)
+                        })
+                        .map_err(|err| {
+                            system_with_internal(
+                                "Failed to clear the terminal",
+                                "Try notifying the developer",
+                                err,
+                            )
+                        }),
+                )
+            }
             Event::Key(KeyEvent {
-                code: KeyCode::Char('p'),
+                code: KeyCode::Char(key),
                 kind: KeyEventKind::Press,
                 ..
-            }) => {
-                *paused = !*paused;
-                let res = if *paused {
-                    crossterm::queue!(
-                        writer,
-                        terminal::BeginSynchronizedUpdate,
-                        cursor::MoveTo(0, 2),
-                        style::Print("Timer is paused. Press 'p' to resume or 'q' to quit."),
-                    )
-                    .and_then(|_| print_paused(writer, paused_print))
+            }) if key == options.keys.pause => {
+                let now = options.clock.now();
+                if state.is_paused() {
+                    state.resume(now);
+                    state.paused_print = true;
                 } else {
-                    crossterm::execute!(
-                        writer,
-                        terminal::BeginSynchronizedUpdate,
-                        cursor::MoveTo(0, 1),
-                        terminal::Clear(terminal::ClearType::CurrentLine),
-                        cursor::MoveToNextLine(1),
-                        terminal::Clear(terminal::ClearType::CurrentLine),
-                        terminal::EndSynchronizedUpdate,
-                    )
+                    state.pause(now);
+                }
+                let res = if options.inline {
+                    render(writer, state, options)
+                } else if state.is_paused() {
+                    queue_taskbar_progress(writer, state, options)
+                        .and_then(|_| queue_paused_hint(writer, state.top(), options))
+                        .and_then(|_| print_paused(writer, state.top(), &mut state.paused_print))
+                } else {
+                    queue_taskbar_progress(writer, state, options)
+                        .and_then(|_| clear_paused(writer, state.top()))
                 }
                 .map_err(|err| {
                     system_with_internal(
@@ -251,6 +3920,91 @@ fn process_event_branch(
                 }
                 ControlFlow::Continue
             }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(key),
+                kind: KeyEventKind::Press,
+                ..
+            }) if key == options.keys.reset || key == options.keys.restart => {
+                let was_paused = state.is_paused();
+                state.reset(options.clock.now());
+                state.laps.clear();
+                if was_paused {
+                    state.paused_print = true;
+                    let res = if options.inline {
+                        render(writer, state, options)
+                    } else {
+                        clear_paused(writer, state.top())
+                    };
+                    if let Err(err) = res {
+                        return ControlFlow::Return(Err(system_with_internal(
+                            "Failed to write to the terminal",
+                            "Try notifying the developer",
+                            err,
+                        )));
+                    }
+                }
+                if key == options.keys.reset {
+                    ControlFlow::Reset
+                } else {
+                    ControlFlow::Continue
+                }
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('+' | '='),
+                kind: KeyEventKind::Press,
+                ..
+            }) if state.has_end => {
+                state.add_time(ADJUST_STEP);
+                ControlFlow::Continue
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('-'),
+                kind: KeyEventKind::Press,
+                ..
+            }) if state.has_end => {
+                state.subtract_time(ADJUST_STEP);
+                ControlFlow::Continue
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('e'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) if state.has_end => {
+                state.edit = Some(Edit::default());
+                redraw(writer, state, options)
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('l'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) if options.mode == Mode::CountUp => {
+                let now = options.clock.now();
+                state.update(now);
+                state.laps.record(state.elapsed());
+                redraw(writer, state, options)
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(MUTE_KEY),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                state.toggle_mute(options.clock.now());
+                redraw(writer, state, options)
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('d'),
+                kind: KeyEventKind::Press,
+                // Not Ctrl+D, which is end of input in a terminal
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                state.detail = state.detail.next();
+                redraw(writer, state, options)
+            }
+            Event::Resize(..) => redraw(writer, state, options),
             _ => ControlFlow::Continue,
         },
         Err(err) => ControlFlow::Return(Err(system_with_internal(
@@ -261,11 +4015,123 @@ fn process_event_branch(
     }
 }
 
-fn print_paused(writer: &mut std::io::Stderr, print: &mut bool) -> io::Result<()> {
+/// Types a key into the new remaining time, setting it on Enter.
+fn process_edit_key(
+    code: KeyCode,
+    writer: &mut TerminalGuard<Output>,
+    state: &mut TimerState,
+    options: &TimerOptions,
+) -> ControlFlow {
+    let Some(edit) = &mut state.edit else {
+        return ControlFlow::Continue;
+    };
+    match code {
+        KeyCode::Char(c) => {
+            edit.input.push(c);
+            edit.error = None;
+        }
+        KeyCode::Backspace => {
+            edit.input.pop();
+            edit.error = None;
+        }
+        KeyCode::Esc => state.edit = None,
+        KeyCode::Enter => match parse_duration(&edit.input) {
+            Ok(remaining) => {
+                state.edit = None;
+                state.update(options.clock.now());
+                state.set_remaining(remaining);
+            }
+            // Only the most specific reason fits on the line
+            Err(err) => edit.error = Some(innermost_description(&err)),
+        },
+        _ => return ControlFlow::Continue,
+    }
+    redraw(writer, state, options)
+}
+
+/// The description of the deepest cause of an error.
+fn innermost_description(mut err: &Error) -> String {
+    while let Error::UserError(.., Some(cause), _) | Error::SystemError(.., Some(cause), _) = err {
+        err = cause;
+    }
+    err.description()
+}
+
+/// Draws the display again after a key changed it, paused or not.
+fn redraw(
+    writer: &mut TerminalGuard<Output>,
+    state: &mut TimerState,
+    options: &TimerOptions,
+) -> ControlFlow {
+    let res = if state.is_paused() {
+        render_paused(writer, state, options)
+    } else {
+        render(writer, state, options)
+    };
+    match res {
+        Ok(()) => ControlFlow::Continue,
+        Err(err) => ControlFlow::Return(Err(system_with_internal(
+            "Failed to write to the terminal",
+            "Try notifying the developer",
+            err,
+        ))),
+    }
+}
+
+/// Draws the display of a paused timer, showing "PAUSED" right away instead of waiting for the
+/// next blink.
+fn render_paused(
+    writer: &mut Output,
+    state: &mut TimerState,
+    options: &TimerOptions,
+) -> io::Result<()> {
+    render(writer, state, options)?;
+    if options.inline {
+        return Ok(());
+    }
+    state.paused_print = true;
+    queue_paused_hint(writer, state.top(), options)?;
+    print_paused(writer, state.top(), &mut state.paused_print)
+}
+
+/// Starts a synchronized update with the hint on how to resume, if it fits on the screen.
+fn queue_paused_hint(writer: &mut Output, top: u16, options: &TimerOptions) -> io::Result<()> {
+    crossterm::queue!(writer, terminal::BeginSynchronizedUpdate)?;
+    if Viewport::current()?.has_row(top + PAUSED_HINT_ROW) {
+        crossterm::queue!(
+            writer,
+            cursor::MoveTo(0, top + PAUSED_HINT_ROW),
+            style::Print(options.keys.paused_hint()),
+        )?;
+    }
+    Ok(())
+}
+
+fn clear_paused(writer: &mut Output, top: u16) -> io::Result<()> {
+    let viewport = Viewport::current()?;
+    crossterm::queue!(writer, terminal::BeginSynchronizedUpdate)?;
+    // Clearing a row below the screen would clear the last one instead
+    for row in [top + PAUSED_ROW, top + PAUSED_HINT_ROW] {
+        if viewport.has_row(row) {
+            crossterm::queue!(
+                writer,
+                cursor::MoveTo(0, row),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+            )?;
+        }
+    }
+    crossterm::execute!(writer, terminal::EndSynchronizedUpdate)
+}
+
+fn print_paused(writer: &mut Output, top: u16, print: &mut bool) -> io::Result<()> {
+    if !Viewport::current()?.has_row(top + PAUSED_ROW) {
+        *print = !*print;
+        return crossterm::execute!(writer, terminal::EndSynchronizedUpdate);
+    }
     if *print {
         crossterm::execute!(
             writer,
-            cursor::MoveTo(0, 1),
+            cursor::MoveTo(0, top + PAUSED_ROW),
             style::Print("PAUSED"),
             terminal::EndSynchronizedUpdate,
         )
@@ -273,7 +4139,7 @@ fn print_paused(writer: &mut std::io::Stderr, print: &mut bool) -> io::Result<()
     } else {
         crossterm::execute!(
             writer,
-            cursor::MoveTo(0, 1),
+            cursor::MoveTo(0, top + PAUSED_ROW),
             terminal::Clear(terminal::ClearType::CurrentLine),
             terminal::EndSynchronizedUpdate,
         )
@@ -281,190 +4147,783 @@ fn print_paused(writer: &mut std::io::Stderr, print: &mut bool) -> io::Result<()
     }
 }
 
+/// The first instant after `now` at which the timer has been running for a whole number of
+/// `period`s.
+fn next_aligned_tick(state: &TimerState, now: Instant, period: Duration) -> Instant {
+    let period = period.as_nanos();
+    let into_period = state.running_time(now).as_nanos() % period;
+    now + Duration::from_nanos(u64::try_from(period - into_period).unwrap_or(u64::MAX))
+}
+
+/// The fraction of `initial_duration` that has already elapsed, in `0.0..=1.0`.
+fn elapsed_ratio(initial_duration: Duration, duration: Duration) -> f64 {
+    if initial_duration.is_zero() {
+        return 0.0;
+    }
+    (initial_duration.saturating_sub(duration)).as_secs_f64() / initial_duration.as_secs_f64()
+}
+
+/// The fraction of `initial_duration` that is still remaining, in `0.0..=1.0`.
+fn remaining_ratio(initial_duration: Duration, duration: Duration) -> f64 {
+    if initial_duration.is_zero() {
+        return 0.0;
+    }
+    duration.min(initial_duration).as_secs_f64() / initial_duration.as_secs_f64()
+}
+
+/// The size of the terminal, which the running display is cut down to fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Viewport {
+    columns: u16,
+    rows: u16,
+}
+
+impl Viewport {
+    fn current() -> io::Result<Self> {
+        let (columns, rows) = terminal::size()?;
+        Ok(Self { columns, rows })
+    }
+
+    /// Whether `row` is on the screen, as the cursor would otherwise stop at the last row
+    /// and draw over it.
+    fn has_row(self, row: u16) -> bool {
+        row < self.rows
+    }
+
+    /// The row the running display starts at given the one below the header, leaving out the
+    /// header if it would push the time off the screen.
+    fn top(self, top: u16) -> u16 {
+        if self.has_row(top + TIME_ROW) { top } else { 0 }
+    }
+
+    /// The width of the progress bar across the whole screen, if there is room for any of it
+    /// between the brackets.
+    fn bar_width(self) -> Option<u16> {
+        (self.columns > 2).then_some(self.columns)
+    }
+}
+
+/// A `[####----]` bar that fills `width` columns, including the brackets.
 #[derive(Debug, Clone, Copy)]
-struct DurationDisplay(Duration);
+struct ProgressBar {
+    ratio: f64,
+    width: u16,
+    /// The color of the filled part, reset right after it.
+    color: Option<style::Color>,
+}
 
-impl fmt::Display for DurationDisplay {
+impl fmt::Display for ProgressBar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use crossterm::style::Stylize;
+
+        let inner = usize::from(self.width.saturating_sub(2));
+        let filled = ((inner as f64 * self.ratio.clamp(0.0, 1.0)).round() as usize).min(inner);
+        let fill = "#".repeat(filled);
+        match self.color {
+            Some(color) => write!(f, "[{}", style::style(fill).with(color))?,
+            None => write!(f, "[{fill}")?,
+        }
+        write!(f, "{:-<empty$}]", "", empty = inner - filled)
+    }
+}
+
+/// How the running display shows durations.
+#[derive(Debug, Clone, Copy, Default)]
+struct DurationStyle {
+    /// Show the milliseconds, like "3.450s".
+    millis: bool,
+    /// Show the hours, minutes and seconds as two digits each, like "01:05:09".
+    fixed_width: bool,
+    /// Leave out the seconds, like "1h 5m", see [`MinutesDisplay`].
+    minutes_only: bool,
+}
+
+impl DurationStyle {
+    fn display(self, duration: Duration) -> StyledDuration {
+        StyledDuration(DurationDisplay(duration), self)
+    }
+
+    /// Displays the time left on a countdown, see [`DurationDisplay::remaining`].
+    fn remaining(self, duration: Duration) -> StyledDuration {
+        if self.minutes_only {
+            return StyledDuration(DurationDisplay::remaining_minutes(duration), self);
+        }
+        StyledDuration(DurationDisplay::remaining(duration, self.millis), self)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StyledDuration(DurationDisplay, DurationStyle);
+
+impl fmt::Display for StyledDuration {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(duration, style) = *self;
+        if style.minutes_only {
+            let minutes = MinutesDisplay(duration);
+            return if style.fixed_width {
+                write!(f, "{minutes:#}")
+            } else {
+                write!(f, "{minutes}")
+            };
+        }
+        let precision = if style.millis { 3 } else { 0 };
+        if style.fixed_width {
+            write!(f, "{duration:#.precision$}")
+        } else {
+            write!(f, "{duration:.precision$}")
+        }
+    }
+}
+
+/// Formats like "1h 2m 3s", or with the alternate flag like "1d 02:03:04".
+///
+/// With the precision set, the seconds include the milliseconds, like "3.450s" for `{:.3}`.
+#[derive(Debug, Clone, Copy)]
+struct DurationDisplay(Duration);
+
+impl DurationDisplay {
+    /// Displays the time left on a countdown, rounded up to whole seconds (or whole milliseconds
+    /// if `millis` are shown) so that it only shows zero once the time is up.
+    fn remaining(duration: Duration, millis: bool) -> Self {
+        let unit = if millis { 1_000_000 } else { 1_000_000_000 };
+        match duration.subsec_nanos() % unit {
+            0 => Self(duration),
+            rest => Self(duration.saturating_add(Duration::from_nanos(u64::from(unit - rest)))),
+        }
+    }
+
+    /// Displays the time left on a countdown rounded up to whole minutes, for [`MinutesDisplay`].
+    fn remaining_minutes(duration: Duration) -> Self {
+        let Self(duration) = Self::remaining(duration, false);
+        match duration.as_secs() % 60 {
+            0 => Self(duration),
+            rest => Self(duration.saturating_add(Duration::from_secs(60 - rest))),
+        }
+    }
+
+    /// Writes the duration, leaving out the seconds unless `with_seconds` is set.
+    fn write(&self, f: &mut fmt::Formatter<'_>, with_seconds: bool) -> fmt::Result {
         let total_seconds = self.0.as_secs();
-        let days = total_seconds / 86400;
+        let weeks = total_seconds / 604800;
+        let days = (total_seconds % 604800) / 86400;
         let hours = (total_seconds % 86400) / 3600;
         let minutes = (total_seconds % 3600) / 60;
         let seconds = total_seconds % 60;
 
-        if days > 0 {
-            write!(f, "{}d ", days)?;
+        if f.alternate() {
+            let days = total_seconds / 86400;
+            if days > 0 {
+                write!(f, "{days}d ")?;
+            }
+            write!(f, "{hours:02}:{minutes:02}")?;
+            if !with_seconds {
+                return Ok(());
+            }
+            write!(f, ":{seconds:02}")?;
+        } else {
+            if weeks > 0 {
+                write!(f, "{weeks}w ")?;
+            }
+            if days > 0 || weeks > 0 {
+                write!(f, "{}d ", days)?;
+            }
+            if hours > 0 || days > 0 || weeks > 0 {
+                write!(f, "{hours}h ")?;
+            }
+            if !with_seconds {
+                return write!(f, "{minutes}m");
+            }
+            if minutes > 0 || hours > 0 || days > 0 || weeks > 0 {
+                write!(f, "{minutes}m ")?;
+            }
+            write!(f, "{seconds}")?;
         }
-        if hours > 0 || days > 0 {
-            write!(f, "{hours}h ")?;
+        if let Some(precision @ 1..) = f.precision() {
+            let precision = precision.min(3);
+            let fraction = self.0.subsec_millis() / 10u32.pow(3 - precision as u32);
+            write!(f, ".{fraction:0precision$}")?;
         }
-        if minutes > 0 || hours > 0 || days > 0 {
-            write!(f, "{minutes}m ")?;
+        if f.alternate() {
+            Ok(())
+        } else {
+            f.write_str("s")
         }
-        write!(f, "{seconds}s")
     }
 }
 
-fn parse_duration(duration_str: &str) -> Result<Duration, Error> {
-    let parts = duration_str.rsplit(':').take(5).collect::<Box<[_]>>();
-    if parts.is_empty() {
-        return Err(user_with_cause(
-            "Failed to parse the duration",
-            "Provide the duration in the following format: \"[[[d:]h:]m:]s\"",
-            user(
-                "Missing parts",
-                "Make sure to provide at least the seconds part of the duration",
-            ),
-        ));
-    }
-    if parts.len() > 4 {
-        return Err(user_with_cause(
-            "Failed to parse the duration",
-            "Provide the duration in the following format: \"[[[d:]h:]m:]s\"",
-            user(
-                "Too many parts",
-                "Make sure to provide at most 4 parts for days, hours, minutes, and seconds",
-            ),
-        ));
+impl fmt::Display for DurationDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, true)
     }
-    let s_ms_part = parts[0];
-
-    let (s_part, ms_part) = {
-        let parts = s_ms_part.split('.').take(3).collect::<Box<[_]>>();
-        match parts.len() {
-            0 => unreachable!(),
-            1 => (parts[0], None),
-            2 => (parts[0], Some(parts[1])),
-            _ => {
-                return Err(user_with_cause(
-                    "Failed to parse the duration",
-                    "Provide the duration in the following format: \"[[[d:]h:]m:]s\"",
-                    user(
-                        "Too many parts in seconds.milliseconds",
-                        "Make sure to provide at most one dot in the seconds part",
-                    ),
-                ));
-            }
-        }
-    };
+}
 
-    let s = Duration::from_secs(s_part.parse().map_err(|err| {
-        user_with_internal(
-            "Failed to parse the seconds part",
-            "Make sure to provide a valid number for the seconds part",
-            err,
-        )
-    })?);
-    let ms = if let Some(ms_part) = ms_part {
-        Duration::from_millis(ms_part.parse().map_err(|err| {
-            user_with_internal(
-                "Failed to parse the milliseconds part",
-                "Make sure to provide a valid number for the milliseconds part",
-                err,
-            )
-        })?)
-    } else {
-        Duration::ZERO
-    };
+/// Formats like "1h 2m", or with the alternate flag like "1d 02:03", leaving out the seconds.
+#[derive(Debug, Clone, Copy)]
+struct MinutesDisplay(DurationDisplay);
 
-    let mut duration = s + ms;
-    for (i, part) in parts.iter().copied().enumerate().skip(1) {
-        let value = part.parse::<u64>().map_err(|err| {
-            user_with_internal(
-                "Failed to parse a duration part",
-                "Make sure to provide a valid number for the duration part",
-                err,
-            )
-        })?;
-        duration = match i {
-            1 => duration
-                .checked_add(Duration::from_secs(value.checked_mul(60).ok_or_else(
-                    || {
-                        user_with_cause(
-                            "Duration overflow",
-                            "The provided duration is too large to be represented",
-                            user(
-                                "Overflow in minutes",
-                                "Make sure the value is within a reasonable range",
-                            ),
-                        )
-                    },
-                )?))
-                .ok_or_else(|| {
-                    user_with_cause(
-                        "Duration overflow",
-                        "The provided duration is too large to be represented",
-                        user(
-                            "Overflow in minutes",
-                            "Make sure the value is within a reasonable range",
-                        ),
-                    )
-                })?, // minutes
-            2 => duration
-                .checked_add(Duration::from_secs(value.checked_mul(3600).ok_or_else(
-                    || {
-                        user_with_cause(
-                            "Duration overflow",
-                            "The provided duration is too large to be represented",
-                            user(
-                                "Overflow in hours",
-                                "Make sure the value is within a reasonable range",
-                            ),
-                        )
-                    },
-                )?))
-                .ok_or_else(|| {
-                    user_with_cause(
-                        "Duration overflow",
-                        "The provided duration is too large to be represented",
-                        user(
-                            "Overflow in hours",
-                            "Make sure the value is within a reasonable range",
-                        ),
-                    )
-                })?, // hours
-            3 => duration
-                .checked_add(Duration::from_secs(value.checked_mul(86400).ok_or_else(
-                    || {
-                        user_with_cause(
-                            "Duration overflow",
-                            "The provided duration is too large to be represented",
-                            user(
-                                "Overflow in days",
-                                "Make sure the value is within a reasonable range",
-                            ),
-                        )
-                    },
-                )?))
-                .ok_or_else(|| {
-                    user_with_cause(
-                        "Duration overflow",
-                        "The provided duration is too large to be represented",
-                        user(
-                            "Overflow in days",
-                            "Make sure the value is within a reasonable range",
-                        ),
-                    )
-                })?, // days
-            _ => {
-                return Err(user(
-                    "Invalid duration part",
-                    "Make sure to provide a valid number for the duration part",
-                ));
-            }
-        };
+impl fmt::Display for MinutesDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write(f, false)
     }
-
-    Ok(duration)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn reject_reserved_keys() {
+        let bind = |pause, quit| KeyBindings::new(pause, quit, 'r', 'R');
+        assert!(bind('p', 'q').is_ok());
+        assert!(bind('p', 'p').unwrap_err().is_user());
+        for key in ['+', '=', '-'] {
+            let err = bind(key, 'q').unwrap_err();
+            assert!(err.is_user(), "{key}");
+            assert!(
+                err.to_string().contains("adjusts the remaining time"),
+                "{key}"
+            );
+        }
+        assert!(
+            bind('p', 'd')
+                .unwrap_err()
+                .to_string()
+                .contains("cycles the details shown")
+        );
+        assert!(
+            bind('p', 'l')
+                .unwrap_err()
+                .to_string()
+                .contains("records a lap")
+        );
+        assert!(
+            bind('p', 'e')
+                .unwrap_err()
+                .to_string()
+                .contains("types a new remaining time")
+        );
+        assert!(bind('s', 'q').unwrap_err().to_string().contains("snoozes"));
+        assert!(bind('p', 'm').unwrap_err().to_string().contains("mutes"));
+    }
+
     #[test]
     fn verify_cli() {
         use clap::CommandFactory;
         Args::command().debug_assert();
     }
+
+    #[test]
+    fn parse_subcommands() {
+        let parse = |args: &[&str]| Args::try_parse_from(["timer"].iter().chain(args));
+        assert!(matches!(
+            parse(&["stats"]).unwrap().command,
+            Some(Command::Stats)
+        ));
+        assert!(matches!(
+            parse(&["--inline", "resume", "timer.json"])
+                .unwrap()
+                .command,
+            Some(Command::Resume { .. })
+        ));
+        // A bare duration still runs the timer, with the options on either side
+        let args = parse(&["--bell", "false", "5:00", "--inline"]).unwrap();
+        assert!(args.command.is_none());
+        assert_eq!(args.durations.len(), 1);
+        assert!(parse(&["5:00", "stats"]).is_err());
+    }
+
+    #[test]
+    fn render_progress_bar() {
+        let bar = |ratio, width| {
+            ProgressBar {
+                ratio,
+                width,
+                color: None,
+            }
+            .to_string()
+        };
+        assert_eq!(bar(0.0, 10), "[--------]");
+        assert_eq!(bar(0.5, 10), "[####----]");
+        assert_eq!(bar(1.0, 10), "[########]");
+        assert_eq!(bar(0.5, 1), "[]");
+        let colored = ProgressBar {
+            ratio: 0.5,
+            width: 10,
+            color: Some(style::Color::Green),
+        }
+        .to_string();
+        assert!(colored.starts_with("[\x1b["), "{colored:?}");
+        assert!(colored.ends_with("####\x1b[39m----]"), "{colored:?}");
+        assert_eq!(elapsed_ratio(Duration::ZERO, Duration::ZERO), 0.0);
+        assert_eq!(remaining_ratio(Duration::ZERO, Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn fit_tiny_terminal() {
+        let tiny = Viewport {
+            columns: 1,
+            rows: 1,
+        };
+        assert_eq!(tiny.top(1), 0);
+        assert!(tiny.has_row(TIME_ROW));
+        assert!(!tiny.has_row(PAUSED_ROW));
+        assert_eq!(tiny.bar_width(), None);
+
+        let empty = Viewport {
+            columns: 0,
+            rows: 0,
+        };
+        assert_eq!(empty.top(1), 0);
+        assert!(!empty.has_row(TIME_ROW));
+        assert_eq!(empty.bar_width(), None);
+
+        let usual = Viewport {
+            columns: 80,
+            rows: 24,
+        };
+        assert_eq!(usual.top(1), 1);
+        assert!(usual.has_row(1 + EDIT_ERROR_ROW));
+        assert_eq!(usual.bar_width(), Some(80));
+    }
+
+    #[test]
+    fn display_milliseconds() {
+        let duration = Duration::from_millis(63_450);
+        assert_eq!(format!("{}", DurationDisplay(duration)), "1m 3s");
+        assert_eq!(format!("{:.3}", DurationDisplay(duration)), "1m 3.450s");
+        assert_eq!(format!("{:.1}", DurationDisplay(duration)), "1m 3.4s");
+        assert_eq!(
+            format!("{:.3}", DurationDisplay::remaining(duration, true)),
+            "1m 3.450s"
+        );
+        assert_eq!(
+            DurationDisplay::remaining(Duration::from_micros(3_449_001), true).0,
+            Duration::from_millis(3_450)
+        );
+        assert_eq!(
+            DurationDisplay::remaining(duration, false).to_string(),
+            "1m 4s"
+        );
+    }
+
+    #[test]
+    fn display_long_durations() {
+        let days = Duration::from_secs(5000 * 86400 + 3723);
+        assert_eq!(DurationDisplay(days).to_string(), "714w 2d 1h 2m 3s");
+        assert_eq!(format!("{:#}", DurationDisplay(days)), "5000d 01:02:03");
+        assert_eq!(
+            format!(
+                "{:#}",
+                MinutesDisplay(DurationDisplay::remaining_minutes(days))
+            ),
+            "5000d 01:03"
+        );
+        assert_eq!(
+            DurationDisplay(Duration::from_secs(u64::MAX)).to_string(),
+            "30500568904943w 0d 7h 0m 15s"
+        );
+        assert_eq!(
+            format!("{:#}", DurationDisplay::remaining(Duration::MAX, false)),
+            "213503982334601d 07:00:15"
+        );
+    }
+
+    #[test]
+    fn display_fixed_width() {
+        let style = DurationStyle {
+            millis: false,
+            fixed_width: true,
+            minutes_only: false,
+        };
+        assert_eq!(
+            style.display(Duration::from_secs(65)).to_string(),
+            "00:01:05"
+        );
+        assert_eq!(
+            style.display(Duration::from_secs(694861)).to_string(),
+            "8d 01:01:01"
+        );
+        let style = DurationStyle {
+            millis: true,
+            ..style
+        };
+        assert_eq!(
+            style.display(Duration::from_millis(3_723_450)).to_string(),
+            "01:02:03.450"
+        );
+    }
+
+    #[test]
+    fn display_minutes_only() {
+        let style = DurationStyle {
+            minutes_only: true,
+            ..DurationStyle::default()
+        };
+        // The time left is rounded up, so that the minutes only run out with the time
+        assert_eq!(style.remaining(Duration::from_secs(60)).to_string(), "1m");
+        assert_eq!(style.remaining(Duration::from_secs(61)).to_string(), "2m");
+        assert_eq!(
+            style
+                .remaining(Duration::from_millis(3_599_500))
+                .to_string(),
+            "1h 0m"
+        );
+        assert_eq!(style.display(Duration::from_secs(119)).to_string(), "1m");
+        let style = DurationStyle {
+            fixed_width: true,
+            ..style
+        };
+        assert_eq!(
+            style.remaining(Duration::from_secs(93_601)).to_string(),
+            "1d 02:01"
+        );
+    }
+
+    #[test]
+    fn display_weeks() {
+        assert_eq!(
+            DurationDisplay(Duration::from_secs(694861)).to_string(),
+            "1w 1d 1h 1m 1s"
+        );
+    }
+
+    #[test]
+    fn segment_header() {
+        let rest = Step {
+            label: Some("Rest".to_owned()),
+            duration: Some(Duration::from_secs(90)),
+        };
+        let segment = |number, label, next| Segment {
+            number,
+            count: 2,
+            label,
+            next,
+        };
+        assert_eq!(
+            segment(1, None, Some(&rest)).header().as_deref(),
+            Some("Segment 1 of 2, next up: Rest (1m 30s)")
+        );
+        assert_eq!(
+            segment(1, Some("Plank"), Some(&rest)).header().as_deref(),
+            Some("Plank (segment 1 of 2), next up: Rest (1m 30s)")
+        );
+        assert_eq!(
+            segment(2, None, None).header().as_deref(),
+            Some("Segment 2 of 2")
+        );
+        let single = Segment {
+            number: 1,
+            count: 1,
+            label: None,
+            next: None,
+        };
+        assert!(single.header().is_none());
+        assert!(single.is_last());
+    }
+
+    #[test]
+    fn reject_long_duration() {
+        let steps = |secs| {
+            vec![Step {
+                label: None,
+                duration: Some(Duration::from_secs(secs)),
+            }]
+        };
+        let max = Some(Duration::from_secs(4 * 3600));
+        assert!(reject_long_durations(steps(4 * 3600), max).is_ok());
+        assert!(reject_long_durations(steps(1000 * 3600), None).is_ok());
+        let err = reject_long_durations(steps(90 * 3600 * 60), max).unwrap_err();
+        assert!(err.is_user());
+        assert!(
+            err.message().contains("like 1h 30m 0s"),
+            "{}",
+            err.message()
+        );
+
+        // Less than a unit over the maximum, only the maximum is stated
+        let err = reject_long_durations(steps(120), Some(Duration::from_secs(60))).unwrap_err();
+        assert!(err.message().contains("at most 1m"), "{}", err.message());
+        assert!(!err.message().contains("like"), "{}", err.message());
+    }
+
+    #[test]
+    fn sample_duration_range() {
+        let range: DurationRange = "1:00..5:00".parse().unwrap();
+        for _ in 0..100 {
+            let duration = range.sample();
+            assert!((Duration::from_secs(60)..=Duration::from_secs(300)).contains(&duration));
+        }
+        let range: DurationRange = "90s..1.5m".parse().unwrap();
+        assert_eq!(range.sample(), Duration::from_secs(90));
+        assert!("5:00..1:00".parse::<DurationRange>().is_err());
+        assert!("0..1:00".parse::<DurationRange>().is_err());
+        assert!("1:00".parse::<DurationRange>().is_err());
+    }
+
+    #[test]
+    fn parse_duration_examples() {
+        for (_, examples) in DURATION_EXAMPLES {
+            for example in *examples {
+                assert!(parse_duration(example).is_ok(), "{example}");
+            }
+        }
+    }
+
+    #[test]
+    fn build_exec_environment() {
+        assert_eq!(
+            exec_env(
+                &Some("Tea".to_owned()),
+                Duration::from_millis(90_500),
+                "stopped"
+            ),
+            [
+                ("TIMER_OUTCOME", "stopped".to_owned()),
+                ("TIMER_ELAPSED", "90".to_owned()),
+                ("TIMER_LABEL", "Tea".to_owned()),
+            ]
+        );
+        assert_eq!(exec_env(&None, Duration::ZERO, "completed")[2].1, "");
+    }
+
+    #[test]
+    fn parse_chained_timer() {
+        let timer = |s: &str| {
+            s.parse::<ChainedTimer>()
+                .map(|ChainedTimer(step)| (step.label, step.duration.unwrap()))
+        };
+        assert_eq!(timer("1:00").unwrap(), (None, Duration::from_secs(60)));
+        assert_eq!(
+            timer("Long break = 15 minutes").unwrap(),
+            (Some("Long break".to_owned()), Duration::from_secs(900))
+        );
+        assert!(timer("=5:00").is_err());
+        assert!(timer("Break=").is_err());
+        assert!(timer("Break").is_err());
+    }
+
+    #[test]
+    fn parse_time_of_day() {
+        let time = |s: &str| s.parse::<TimeOfDay>().map(|TimeOfDay(time)| time);
+        let hms = |h, m, s| NaiveTime::from_hms_opt(h, m, s).unwrap();
+        assert_eq!(time("17:00").unwrap(), hms(17, 0, 0));
+        assert_eq!(time("17:00:30").unwrap(), hms(17, 0, 30));
+        assert_eq!(time("5:00 PM").unwrap(), hms(17, 0, 0));
+        assert_eq!(time("5:00:30 pm").unwrap(), hms(17, 0, 30));
+        assert_eq!(time("12:15 AM").unwrap(), hms(0, 15, 0));
+        assert_eq!(time("12:15 PM").unwrap(), hms(12, 15, 0));
+        for input in ["25:00", "13:00 PM", "5 PM", "5:00 XM"] {
+            assert!(time(input).unwrap_err().is_user(), "{input}");
+        }
+    }
+
+    #[test]
+    fn count_down_to_date_and_time() {
+        let datetime = |s: &str| s.parse::<LocalDateTime>();
+        assert!(datetime("2025-12-31 23:59").is_ok());
+        assert!(datetime("2025-12-31 23:59:30").is_ok());
+        assert!(datetime("2025-12-31T23:59").is_ok());
+        for input in [
+            "2025-12-31",
+            "23:59",
+            "2025-13-01 00:00",
+            "31.12.2025 23:59",
+        ] {
+            assert!(datetime(input).unwrap_err().is_user(), "{input}");
+        }
+
+        // Through the local time zone, whatever daylight saving time does in between
+        let now = local_time(datetime("2026-07-01 12:00").unwrap().0).unwrap();
+        let target = datetime("2026-07-04 12:30:15").unwrap();
+        let expected = (local_time(target.0).unwrap() - now).to_std().unwrap();
+        assert_eq!(target.duration_from(now).unwrap(), expected);
+        let err = datetime("2026-07-01 11:00")
+            .unwrap()
+            .duration_from(now)
+            .unwrap_err();
+        assert!(err.is_user());
+        assert!(err.to_string().contains("--until"));
+    }
+
+    #[test]
+    fn reject_zero_duration() {
+        let steps = |duration| {
+            vec![Step {
+                label: None,
+                duration,
+            }]
+        };
+        assert!(
+            reject_zero_durations(steps(Some(Duration::ZERO)), Mode::CountDown)
+                .unwrap_err()
+                .is_user()
+        );
+        assert!(reject_zero_durations(steps(Some(Duration::ZERO)), Mode::CountUp).is_ok());
+        assert!(reject_zero_durations(steps(None), Mode::CountUp).is_ok());
+        assert!(
+            reject_zero_durations(steps(Some(Duration::from_millis(1))), Mode::CountDown).is_ok()
+        );
+    }
+
+    #[test]
+    fn finish_sub_tick_duration() {
+        let start = Instant::now();
+        let mut state = TimerState::new(Some(Duration::from_millis(500)), start);
+        state.update(start);
+        assert_eq!(state.duration, Duration::from_millis(500));
+        assert_eq!(
+            DurationDisplay::remaining(state.duration, false).to_string(),
+            "1s"
+        );
+
+        // The next tick lands past the deadline
+        state.update(start + Duration::from_secs(1));
+        assert!(state.duration.is_zero());
+        assert_eq!(state.elapsed(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn pause_countdown() {
+        let start = Instant::now();
+        let secs = Duration::from_secs;
+        let mut countdown = Countdown::new(secs(10), start);
+        assert_eq!(countdown.deadline(), Some(start + secs(10)));
+        countdown.toggle_pause(start + secs(3));
+        assert_eq!(countdown.deadline(), None);
+        assert_eq!(countdown.remaining(start + secs(8)), secs(7));
+        countdown.toggle_pause(start + secs(8));
+        assert_eq!(countdown.deadline(), Some(start + secs(15)));
+        assert_eq!(countdown.elapsed(start + secs(9)), secs(4));
+        assert_eq!(countdown.remaining(start + secs(20)), Duration::ZERO);
+    }
+
+    #[test]
+    fn toggle_mute() {
+        let start = Instant::now();
+        let mut state = TimerState::new(Some(Duration::from_secs(60)), start);
+        assert_eq!(state.mute_banner(start), None);
+        state.toggle_mute(start);
+        assert!(state.muted);
+        assert_eq!(
+            state.mute_banner(start + Duration::from_secs(1)),
+            Some("Muted")
+        );
+        assert_eq!(state.mute_banner(start + MUTE_BANNER_TIME), None);
+        state.toggle_mute(start);
+        assert!(!state.muted);
+        assert_eq!(state.mute_banner(start), Some("Unmuted"));
+    }
+
+    #[test]
+    fn announce_milestones_once() {
+        let start = Instant::now();
+        let mut milestones = Milestones::new(
+            &[10, 300, 60].map(Duration::from_secs),
+            Duration::from_secs(90),
+        );
+        // 300s was never ahead of a 90s timer
+        assert_eq!(milestones.reach(Duration::from_secs(70), start), None);
+        assert_eq!(
+            milestones.reach(Duration::from_secs(60), start),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(milestones.reach(Duration::from_millis(59_900), start), None);
+        assert_eq!(
+            milestones.banner(start + Duration::from_secs(1)),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(milestones.banner(start + MILESTONE_BANNER_TIME), None);
+        assert_eq!(
+            milestones.reach(Duration::from_millis(9_500), start),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(milestones.reach(Duration::ZERO, start), None);
+
+        milestones.rearm(Duration::from_secs(90));
+        assert_eq!(
+            milestones.reach(Duration::from_secs(5), start),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn align_ticks_to_running_time() {
+        let start = Instant::now();
+        let mut state = TimerState::new(Some(Duration::from_secs(10)), start);
+        let second = Duration::from_secs(1);
+        assert_eq!(
+            next_aligned_tick(&state, start + Duration::from_millis(1300), second),
+            start + Duration::from_secs(2)
+        );
+        assert_eq!(
+            next_aligned_tick(&state, start + second, second),
+            start + Duration::from_secs(2)
+        );
+        state.pause(start + Duration::from_millis(1300));
+        state.resume(start + Duration::from_millis(2000));
+        assert_eq!(
+            next_aligned_tick(&state, start + Duration::from_millis(2100), second),
+            start + Duration::from_millis(2700)
+        );
+    }
+
+    #[test]
+    fn exclude_paused_time() {
+        let start = Instant::now();
+        let mut state = TimerState::new(Some(Duration::from_secs(60)), start);
+        state.pause(start + Duration::from_secs(2));
+        assert_eq!(
+            state.running_time(start + Duration::from_secs(4)),
+            Duration::from_secs(2)
+        );
+        state.resume(start + Duration::from_secs(5));
+        assert_eq!(
+            state.running_time(start + Duration::from_secs(7)),
+            Duration::from_secs(4)
+        );
+        assert_eq!(
+            state.paused_time(start + Duration::from_secs(7)),
+            Duration::from_secs(3)
+        );
+    }
+
+    #[test]
+    fn compute_remaining_from_deadline() {
+        let start = Instant::now();
+        let mut state = TimerState::new(Some(Duration::from_secs(10)), start);
+        state.update(start + Duration::from_millis(2500));
+        assert_eq!(state.duration, Duration::from_millis(7500));
+        assert_eq!(
+            DurationDisplay::remaining(state.duration, false).to_string(),
+            "8s"
+        );
+
+        state.pause(start + Duration::from_secs(3));
+        state.resume(start + Duration::from_secs(8));
+        assert_eq!(
+            state.deadline(start + Duration::from_secs(8)),
+            Some(start + Duration::from_secs(15))
+        );
+        state.update(start + Duration::from_secs(9));
+        assert_eq!(state.duration, Duration::from_secs(6));
+        state.update(start + Duration::from_secs(20));
+        assert!(state.duration.is_zero());
+    }
+
+    #[test]
+    fn set_remaining_time() {
+        let start = Instant::now();
+        let mut state = TimerState::new(Some(Duration::from_secs(60)), start);
+        state.update(start + Duration::from_secs(20));
+        state.set_remaining(Duration::from_secs(300));
+        state.update(start + Duration::from_secs(20));
+        assert_eq!(state.duration, Duration::from_secs(300));
+        assert_eq!(state.elapsed(), Duration::from_secs(20));
+        state.set_remaining(Duration::from_secs(5));
+        state.update(start + Duration::from_secs(21));
+        assert_eq!(state.duration, Duration::from_secs(4));
+
+        let err = parse_duration("1:x").unwrap_err();
+        assert_ne!(innermost_description(&err), err.description());
+    }
 }
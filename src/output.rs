@@ -0,0 +1,86 @@
+use core::str::FromStr;
+use std::io::{self, Write};
+
+use crossterm::tty::IsTty;
+use human_errors::{Error, user};
+
+/// The stream the running display is drawn on, chosen with `--output`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    #[default]
+    Stderr,
+}
+
+impl OutputStream {
+    pub fn writer(self) -> Output {
+        match self {
+            Self::Stdout => Output::Stdout(io::stdout()),
+            Self::Stderr => Output::Stderr(io::stderr()),
+        }
+    }
+}
+
+impl FromStr for OutputStream {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stdout" => Ok(Self::Stdout),
+            "stderr" => Ok(Self::Stderr),
+            _ => Err(user(
+                "Unknown output stream",
+                "Provide either \"stdout\" or \"stderr\"",
+            )),
+        }
+    }
+}
+
+/// A handle to the stream picked with [`OutputStream`].
+pub enum Output {
+    Stdout(io::Stdout),
+    Stderr(io::Stderr),
+}
+
+impl Output {
+    pub fn is_tty(&self) -> bool {
+        match self {
+            Self::Stdout(stdout) => stdout.is_tty(),
+            Self::Stderr(stderr) => stderr.is_tty(),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(stdout) => stdout.write(buf),
+            Self::Stderr(stderr) => stderr.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::Stderr(stderr) => stderr.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_output_stream() {
+        assert_eq!(
+            "stdout".parse::<OutputStream>().ok(),
+            Some(OutputStream::Stdout)
+        );
+        assert_eq!(
+            "stderr".parse::<OutputStream>().ok(),
+            Some(OutputStream::Stderr)
+        );
+        assert!("stdin".parse::<OutputStream>().is_err());
+    }
+}
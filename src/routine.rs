@@ -0,0 +1,178 @@
+use core::time::Duration;
+use std::{io::Read, path::Path};
+
+use human_errors::{Error, user, user_with_cause, user_with_internal};
+use timer::parse_duration;
+
+use crate::Step;
+
+const ROUTINE_ADVICE: &str =
+    "Write each timer on its own line as a label followed by a duration (e.g., \"Plank 1:00\")";
+
+/// Reads the timers to run one after another from a routine file.
+pub fn read(path: &Path) -> Result<Vec<Step>, Error> {
+    let contents = std::fs::read_to_string(path).map_err(|err| {
+        user_with_internal(
+            &format!("Failed to read {}", path.display()),
+            "Make sure the file exists and is readable",
+            err,
+        )
+    })?;
+    parse(&contents)
+}
+
+/// Reads the durations to run one after another from stdin, one per line.
+pub fn read_stdin() -> Result<Vec<Duration>, Error> {
+    let mut contents = String::new();
+    std::io::stdin()
+        .read_to_string(&mut contents)
+        .map_err(|err| {
+            user_with_internal(
+                "Failed to read the durations from stdin",
+                "Pipe the durations into the timer as text, one per line",
+                err,
+            )
+        })?;
+    parse_durations(&contents)
+}
+
+/// Parses one duration per line, skipping blank lines and `#` comments.
+fn parse_durations(contents: &str) -> Result<Vec<Duration>, Error> {
+    let mut durations = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let duration = parse_duration(line).map_err(|err| {
+            user_with_cause(
+                &format!("Failed to parse line {} of stdin", index + 1),
+                "Write each duration on its own line (e.g., \"1:00\")",
+                err,
+            )
+        })?;
+        durations.push(duration);
+    }
+
+    if durations.is_empty() {
+        return Err(user(
+            "There are no durations in stdin",
+            "Pipe the durations into the timer, one per line (e.g., `printf '1:00\\n2:00\\n' | timer --stdin`)",
+        ));
+    }
+    Ok(durations)
+}
+
+/// Parses lines like "Plank 1:00", skipping blank lines and `#` comments.
+///
+/// The duration starts at the first word that begins a valid duration, so both the label and
+/// a duration in unit words, like "Rest 5 minutes", may contain spaces.
+fn parse(contents: &str) -> Result<Vec<Step>, Error> {
+    let mut steps = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_error = |cause| {
+            user_with_cause(
+                &format!("Failed to parse line {} of the routine file", index + 1),
+                ROUTINE_ADVICE,
+                cause,
+            )
+        };
+        let duration_start = line
+            .char_indices()
+            .filter(|&(index, c)| {
+                !c.is_whitespace() && line[..index].ends_with(char::is_whitespace)
+            })
+            .map(|(index, _)| index)
+            .find(|&index| parse_duration(&line[index..]).is_ok());
+        let split = match duration_start {
+            Some(index) => Some(line.split_at(index)),
+            // Report the error of the last word, most likely the malformed duration
+            None => line.rsplit_once(char::is_whitespace),
+        };
+        let Some((label, duration)) = split else {
+            return Err(line_error(user(
+                &format!("Expected a label and a duration, found \"{line}\""),
+                "Put a label before the duration",
+            )));
+        };
+        let duration = parse_duration(duration).map_err(line_error)?;
+        steps.push(Step {
+            label: Some(label.trim_end().to_owned()),
+            duration: Some(duration),
+        });
+    }
+
+    if steps.is_empty() {
+        return Err(user("The routine file has no timers in it", ROUTINE_ADVICE));
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_routine() {
+        let steps = parse("# Warm-up\n\nJumping jacks 0:30\n  Rest   10s  \nPlank 1:00\n").unwrap();
+        let steps: Vec<_> = steps
+            .iter()
+            .map(|step| (step.label.as_deref().unwrap(), step.duration.unwrap()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                ("Jumping jacks", Duration::from_secs(30)),
+                ("Rest", Duration::from_secs(10)),
+                ("Plank", Duration::from_secs(60)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_routine_with_word_durations() {
+        let steps = parse(
+            "Rest 5 minutes
+Round 2 1h 30m
+Set 3 90s
+",
+        )
+        .unwrap();
+        let steps: Vec<_> = steps
+            .iter()
+            .map(|step| (step.label.as_deref().unwrap(), step.duration.unwrap()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                ("Rest", Duration::from_secs(300)),
+                ("Round 2", Duration::from_secs(5400)),
+                ("Set 3", Duration::from_secs(90)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_malformed_lines() {
+        for input in ["Plank", "Plank 1:xx", "# only a comment\n", ""] {
+            assert!(parse(input).unwrap_err().is_user(), "{input:?}");
+        }
+        let err = parse("Rest 10\nPlank one").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+    }
+
+    #[test]
+    fn parse_duration_lines() {
+        assert_eq!(
+            parse_durations("1:00\n\n# Break\n  5 minutes  \n").unwrap(),
+            [Duration::from_secs(60), Duration::from_secs(300)]
+        );
+        assert!(parse_durations("# nothing\n").unwrap_err().is_user());
+        let err = parse_durations("1:00\nsoon").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+    }
+}
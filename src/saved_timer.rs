@@ -0,0 +1,95 @@
+use core::time::Duration;
+use std::{fs, io, path::Path};
+
+use human_errors::{Error, user_with_cause, user_with_internal};
+use serde::{Deserialize, Serialize};
+
+use crate::status::millis;
+
+/// A timer stopped by the user, saved with `--save-state` to be picked up again with `--resume`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedTimer {
+    pub remaining_ms: u64,
+    pub label: Option<String>,
+}
+
+impl SavedTimer {
+    pub fn new(remaining: Duration, label: Option<String>) -> Self {
+        Self {
+            remaining_ms: millis(remaining),
+            label,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        Duration::from_millis(self.remaining_ms)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        serde_json::to_vec(self)
+            .map_err(io::Error::from)
+            .and_then(|contents| fs::write(path, contents))
+            .map_err(|err| {
+                user_with_internal(
+                    &format!("Failed to save the timer to {}", path.display()),
+                    "Make sure the directory exists and is writable",
+                    err,
+                )
+            })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let contents = fs::read(path).map_err(|err| {
+            user_with_internal(
+                &format!("Failed to read the saved timer from {}", path.display()),
+                "Make sure the file exists and is readable",
+                err,
+            )
+        })?;
+        serde_json::from_slice(&contents).map_err(|err| {
+            user_with_cause(
+                &format!("Failed to resume the timer from {}", path.display()),
+                "Provide a file written by --save-state",
+                user_with_internal(
+                    "The saved timer is malformed",
+                    "Make sure the file wasn't edited by hand",
+                    err,
+                ),
+            )
+        })
+    }
+
+    /// Deletes the saved timer once it has run out, so that it isn't resumed by accident.
+    pub fn remove(path: &Path) -> Result<(), Error> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(user_with_internal(
+                &format!("Failed to delete the saved timer at {}", path.display()),
+                "Make sure the file is writable, or delete it by hand",
+                err,
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("timer-saved-{}.json", std::process::id()));
+        SavedTimer::new(Duration::from_millis(61_500), Some("Tea".to_owned()))
+            .save(&path)
+            .unwrap();
+
+        let saved = SavedTimer::load(&path).unwrap();
+        assert_eq!(saved.remaining(), Duration::from_millis(61_500));
+        assert_eq!(saved.label.as_deref(), Some("Tea"));
+
+        SavedTimer::remove(&path).unwrap();
+        assert!(!path.exists());
+        SavedTimer::remove(&path).unwrap();
+        assert!(SavedTimer::load(&path).unwrap_err().is_user());
+    }
+}
@@ -0,0 +1,71 @@
+use core::time::Duration;
+use std::{fs::File, io::BufReader, path::Path};
+
+use human_errors::{Error, system_with_internal, user_with_cause, user_with_internal};
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink};
+
+/// A sound file that is being played on the default output device.
+pub struct Sound {
+    // Playback stops once the stream is dropped
+    _stream: OutputStream,
+    sink: Sink,
+}
+
+impl Sound {
+    /// Starts playing the wav or mp3 file at `path`, over and over again if `looped` is set.
+    pub fn play(path: &Path, looped: bool) -> Result<Self, Error> {
+        let advice = "Provide an existing wav or mp3 file with --sound";
+        let file = File::open(path).map_err(|err| {
+            user_with_cause(
+                "Failed to play the sound",
+                advice,
+                user_with_internal(
+                    &format!("Failed to open {}", path.display()),
+                    "Make sure the file exists and is readable",
+                    err,
+                ),
+            )
+        })?;
+        let file = BufReader::new(file);
+        let decoded = if looped {
+            Decoder::new_looped(file)
+                .map(|decoder| Box::new(decoder) as Box<dyn rodio::Source + Send>)
+        } else {
+            Decoder::new(file).map(|decoder| Box::new(decoder) as Box<dyn rodio::Source + Send>)
+        };
+        let source = decoded.map_err(|err| {
+            user_with_cause(
+                "Failed to play the sound",
+                advice,
+                user_with_internal(
+                    &format!("Failed to decode {}", path.display()),
+                    "Make sure the file is a supported audio format",
+                    err,
+                ),
+            )
+        })?;
+
+        let mut stream = OutputStreamBuilder::open_default_stream().map_err(|err| {
+            system_with_internal(
+                "Failed to open the audio output",
+                "Make sure an audio output device is available",
+                err,
+            )
+        })?;
+        stream.log_on_drop(false);
+        let sink = Sink::connect_new(stream.mixer());
+        sink.append(source);
+
+        Ok(Self {
+            _stream: stream,
+            sink,
+        })
+    }
+
+    /// Waits for the sound to finish playing, which a looped sound never does.
+    pub async fn finished(&self) {
+        while !self.sink.empty() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use core::time::Duration;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::TimerState;
+
+/// A machine-readable update about a running timer, printed as a line of JSON.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StatusEvent {
+    Tick {
+        /// Missing for a stopwatch without a duration.
+        remaining_ms: Option<u64>,
+        elapsed_ms: u64,
+        paused: bool,
+    },
+    Repetition {
+        repetition: u32,
+    },
+    Finished {
+        elapsed_ms: u64,
+    },
+    /// The timer was stopped before it ran out.
+    Stopped {
+        elapsed_ms: u64,
+    },
+}
+
+impl StatusEvent {
+    pub fn tick(state: &TimerState) -> Self {
+        Self::Tick {
+            remaining_ms: state.has_end.then(|| millis(state.duration)),
+            elapsed_ms: millis(state.elapsed()),
+            paused: state.is_paused(),
+        }
+    }
+
+    /// Writes the event as a single line of JSON.
+    pub fn write_line(&self, writer: &mut impl Write) -> io::Result<()> {
+        serde_json::to_writer(&mut *writer, self)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+pub fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_events() {
+        let mut output = Vec::new();
+        StatusEvent::Tick {
+            remaining_ms: Some(1500),
+            elapsed_ms: 500,
+            paused: false,
+        }
+        .write_line(&mut output)
+        .unwrap();
+        StatusEvent::Finished { elapsed_ms: 2000 }
+            .write_line(&mut output)
+            .unwrap();
+        StatusEvent::Stopped { elapsed_ms: 700 }
+            .write_line(&mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"event\":\"tick\",\"remaining_ms\":1500,\"elapsed_ms\":500,\"paused\":false}\n\
+             {\"event\":\"finished\",\"elapsed_ms\":2000}\n\
+             {\"event\":\"stopped\",\"elapsed_ms\":700}\n"
+        );
+    }
+}
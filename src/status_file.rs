@@ -0,0 +1,54 @@
+use core::fmt;
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use human_errors::{Error, user_with_internal};
+
+/// A file that holds the current time of a running timer, for status bars to read.
+///
+/// Failing to write the file doesn't stop the timer,
+/// so only the first failure is kept to be reported once the timer is done.
+pub struct StatusFile {
+    path: PathBuf,
+    temp_path: PathBuf,
+    error: Option<io::Error>,
+}
+
+impl StatusFile {
+    pub fn new(path: PathBuf) -> Self {
+        let mut temp_path = OsString::from(path.as_os_str());
+        temp_path.push(".tmp");
+        Self {
+            path,
+            temp_path: PathBuf::from(temp_path),
+            error: None,
+        }
+    }
+
+    /// Replaces the contents of the file with `contents`,
+    /// writing to a temporary file first so readers never see a partial write.
+    pub fn write(&mut self, contents: impl fmt::Display) {
+        if let Err(err) = write_atomically(&self.path, &self.temp_path, &contents.to_string()) {
+            self.error.get_or_insert(err);
+        }
+    }
+
+    /// Takes the first failure to write the file, if there was one.
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take().map(|err| {
+            user_with_internal(
+                &format!("Failed to write the status file {}", self.path.display()),
+                "Make sure the directory exists and is writable",
+                err,
+            )
+        })
+    }
+}
+
+fn write_atomically(path: &Path, temp_path: &Path, contents: &str) -> io::Result<()> {
+    fs::write(temp_path, contents)?;
+    fs::rename(temp_path, path)
+}
@@ -0,0 +1,72 @@
+use std::{
+    fs,
+    io::{self, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::status::StatusEvent;
+
+/// A Unix socket that sends the [`StatusEvent`]s of the running timer to every connected client,
+/// as the same lines of JSON that `--json` prints, for frontends built on top of the timer.
+///
+/// Clients that disconnect, or stop reading, are dropped without affecting the timer.
+pub struct StatusSocket {
+    path: PathBuf,
+    clients: Arc<Mutex<Vec<UnixStream>>>,
+}
+
+impl StatusSocket {
+    /// Starts accepting clients at `path`,
+    /// replacing a socket left behind by a timer that didn't exit cleanly.
+    pub fn bind(path: PathBuf) -> io::Result<Self> {
+        let listener = match UnixListener::bind(&path) {
+            Err(err)
+                if err.kind() == io::ErrorKind::AddrInUse
+                    && fs::symlink_metadata(&path)?.file_type().is_socket()
+                    && UnixStream::connect(&path).is_err() =>
+            {
+                fs::remove_file(&path)?;
+                UnixListener::bind(&path)?
+            }
+            res => res?,
+        };
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&clients);
+        // Accepting blocks, so the thread is left to end with the process
+        std::thread::spawn(move || {
+            for client in listener.incoming().flatten() {
+                // A client that stops reading must not hold up the timer
+                if client.set_nonblocking(true).is_ok() {
+                    accepted
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(client);
+                }
+            }
+        });
+        Ok(Self { path, clients })
+    }
+
+    /// Sends the event to every client, dropping the ones that can't take it.
+    pub fn send(&self, event: StatusEvent) {
+        let mut line = Vec::new();
+        if event.write_line(&mut line).is_err() {
+            return;
+        }
+        self.clients
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain_mut(|client| client.write_all(&line).is_ok());
+    }
+}
+
+impl Drop for StatusSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::{self, BufRead};
+
+use tokio::sync::mpsc;
+
+/// Keys read from stdin one per line, so that scripts can control the timer, e.g., with
+/// `printf 'p\n'` into a pipe.
+pub struct StdinCommands(Option<mpsc::UnboundedReceiver<io::Result<char>>>);
+
+impl StdinCommands {
+    /// Starts reading stdin if `enabled`, otherwise no command ever arrives.
+    pub fn new(enabled: bool) -> Self {
+        if !enabled {
+            return Self(None);
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        // A blocking read can't be cancelled, so the thread is left to end with the process
+        std::thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let command = match line {
+                    Ok(line) => match parse_command(&line) {
+                        Some(command) => Ok(command),
+                        None => continue,
+                    },
+                    Err(err) => Err(err),
+                };
+                let failed = command.is_err();
+                if sender.send(command).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Self(Some(receiver))
+    }
+
+    /// Waits for the next key, which never arrives once stdin is closed.
+    pub async fn next(&mut self) -> io::Result<char> {
+        if let Some(receiver) = &mut self.0
+            && let Some(command) = receiver.recv().await
+        {
+            return command;
+        }
+        self.0 = None;
+        core::future::pending().await
+    }
+}
+
+/// A line holding a single key, ignoring the surrounding whitespace.
+fn parse_command(line: &str) -> Option<char> {
+    let mut chars = line.trim().chars();
+    let command = chars.next()?;
+    chars.next().is_none().then_some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_single_keys() {
+        assert_eq!(parse_command("p"), Some('p'));
+        assert_eq!(parse_command("  q\r"), Some('q'));
+        assert_eq!(parse_command(""), None);
+        assert_eq!(parse_command("pause"), None);
+    }
+}
@@ -0,0 +1,224 @@
+use core::{fmt, time::Duration};
+
+use crate::{DurationDisplay, Repeat, Step};
+
+/// The table printed at the end of a session of several timers, like:
+///
+/// ```text
+/// #  Label  Planned  Actual  Status
+/// 1  Plank  1m 0s    1m 0s   completed
+/// 2  Rest   30s      12s     stopped
+/// 3  -      1m 0s    -       skipped
+///    Total  2m 30s   1m 12s
+/// ```
+pub struct Summary<'a> {
+    rows: Vec<Row<'a>>,
+    /// The planned and actual time spent between the timers with `--gap`, if any.
+    gaps: Option<(Duration, Duration)>,
+}
+
+struct Row<'a> {
+    label: Option<&'a str>,
+    /// `None` for a timer that never runs out.
+    planned: Option<Duration>,
+    /// `None` until the timer has run.
+    actual: Option<Duration>,
+    status: Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Completed,
+    Stopped,
+    Skipped,
+}
+
+impl<'a> Summary<'a> {
+    /// Starts with every step skipped, planning for all of the repetitions of each.
+    pub fn new(steps: &'a [Step], repeat: Repeat) -> Self {
+        let rows = steps
+            .iter()
+            .map(|step| Row {
+                label: step.label.as_deref(),
+                planned: step.duration.and_then(|duration| match repeat {
+                    Repeat::Times(times) => duration.checked_mul(times.max(1)),
+                    Repeat::Infinite => None,
+                }),
+                actual: None,
+                status: Status::Skipped,
+            })
+            .collect();
+        Self { rows, gaps: None }
+    }
+
+    /// Plans for a `gap` between each two steps, adding a row for the gaps below the total.
+    pub fn with_gap(mut self, gap: Option<Duration>) -> Self {
+        let count = u32::try_from(self.rows.len().saturating_sub(1)).unwrap_or(u32::MAX);
+        self.gaps = gap.map(|gap| (gap.saturating_mul(count), Duration::ZERO));
+        self
+    }
+
+    /// Adds the time spent in a gap between two steps.
+    pub fn gap(&mut self, elapsed: Duration) {
+        if let Some((_, actual)) = &mut self.gaps {
+            *actual = actual.saturating_add(elapsed);
+        }
+    }
+
+    pub fn complete(&mut self, index: usize, elapsed: Duration) {
+        self.record(index, elapsed, Status::Completed);
+    }
+
+    pub fn stop(&mut self, index: usize, elapsed: Duration) {
+        self.record(index, elapsed, Status::Stopped);
+    }
+
+    /// Marks every step as skipped again, when the session starts over.
+    pub fn clear(&mut self) {
+        for row in &mut self.rows {
+            row.actual = None;
+            row.status = Status::Skipped;
+        }
+        if let Some((_, actual)) = &mut self.gaps {
+            *actual = Duration::ZERO;
+        }
+    }
+
+    fn record(&mut self, index: usize, elapsed: Duration, status: Status) {
+        if let Some(row) = self.rows.get_mut(index) {
+            row.actual = Some(elapsed);
+            row.status = status;
+        }
+    }
+}
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let duration = |duration: Option<Duration>| {
+            duration.map_or_else(
+                || "-".to_owned(),
+                |duration| DurationDisplay(duration).to_string(),
+            )
+        };
+        let planned_total = self
+            .rows
+            .iter()
+            .filter_map(|row| row.planned)
+            .fold(Duration::ZERO, Duration::saturating_add);
+        let actual_total = self
+            .rows
+            .iter()
+            .filter_map(|row| row.actual)
+            .fold(Duration::ZERO, Duration::saturating_add);
+
+        let mut lines = vec![[
+            "#".to_owned(),
+            "Label".to_owned(),
+            "Planned".to_owned(),
+            "Actual".to_owned(),
+            "Status".to_owned(),
+        ]];
+        lines.extend(self.rows.iter().enumerate().map(|(index, row)| {
+            [
+                (index + 1).to_string(),
+                row.label.unwrap_or("-").to_owned(),
+                duration(row.planned),
+                duration(row.actual),
+                match row.status {
+                    Status::Completed => "completed",
+                    Status::Stopped => "stopped",
+                    Status::Skipped => "skipped",
+                }
+                .to_owned(),
+            ]
+        }));
+        lines.push([
+            String::new(),
+            "Total".to_owned(),
+            duration(Some(planned_total)),
+            duration(Some(actual_total)),
+            String::new(),
+        ]);
+        if let Some((planned, actual)) = self.gaps {
+            lines.push([
+                String::new(),
+                "Gaps".to_owned(),
+                duration(Some(planned)),
+                duration(Some(actual)),
+                String::new(),
+            ]);
+        }
+
+        let mut widths = [0; 5];
+        for line in &lines {
+            for (width, cell) in widths.iter_mut().zip(line) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for line in &lines {
+            let mut line = line
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            line.truncate(line.trim_end().len());
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_table() {
+        let step = |label: Option<&str>, secs| Step {
+            label: label.map(str::to_owned),
+            duration: Some(Duration::from_secs(secs)),
+        };
+        let steps = [
+            step(Some("Plank"), 60),
+            step(Some("Rest"), 30),
+            step(None, 60),
+        ];
+        let mut summary = Summary::new(&steps, Repeat::Times(1));
+        summary.complete(0, Duration::from_secs(60));
+        summary.stop(1, Duration::from_secs(12));
+        assert_eq!(
+            summary.to_string(),
+            "#  Label  Planned  Actual  Status\n\
+             1  Plank  1m 0s    1m 0s   completed\n\
+             2  Rest   30s      12s     stopped\n\
+             3  -      1m 0s    -       skipped\n   \
+             Total  2m 30s   1m 12s\n"
+        );
+
+        summary.clear();
+        assert!(
+            summary
+                .to_string()
+                .contains("1  Plank  1m 0s    -       skipped")
+        );
+    }
+
+    #[test]
+    fn render_gaps() {
+        let step = |secs| Step {
+            label: None,
+            duration: Some(Duration::from_secs(secs)),
+        };
+        let steps = [step(60), step(30), step(60)];
+        let mut summary =
+            Summary::new(&steps, Repeat::Times(1)).with_gap(Some(Duration::from_secs(10)));
+        summary.complete(0, Duration::from_secs(60));
+        summary.gap(Duration::from_secs(10));
+        summary.stop(1, Duration::from_secs(12));
+        assert!(summary.to_string().ends_with(
+            "   Total  2m 30s   1m 12s\n   \
+                Gaps   20s      10s\n"
+        ));
+    }
+}
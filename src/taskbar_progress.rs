@@ -0,0 +1,67 @@
+use core::fmt;
+
+/// The progress shown on the taskbar by terminals like Windows Terminal and ConEmu,
+/// set with the `OSC 9;4` sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskbarProgress {
+    /// No progress at all.
+    Hidden,
+    /// The percentage of the timer that has elapsed.
+    Running(u8),
+    /// The percentage of a paused timer that has elapsed, usually shown in yellow.
+    Paused(u8),
+}
+
+impl TaskbarProgress {
+    /// The progress of a running or paused timer with `ratio` of it elapsed.
+    pub fn new(ratio: f64, paused: bool) -> Self {
+        let percent = (ratio.clamp(0.0, 1.0) * 100.0).round() as u8;
+        if paused {
+            Self::Paused(percent)
+        } else {
+            Self::Running(percent)
+        }
+    }
+}
+
+impl crossterm::Command for TaskbarProgress {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let (state, percent) = match *self {
+            Self::Hidden => (0, 0),
+            Self::Running(percent) => (1, percent),
+            Self::Paused(percent) => (4, percent),
+        };
+        write!(f, "\x1b]9;4;{state};{percent}\x1b\\")
+    }
+
+    /// The legacy Windows console has no taskbar progress to set.
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::Command;
+
+    use super::*;
+
+    #[test]
+    fn write_progress_sequence() {
+        let ansi = |progress: TaskbarProgress| {
+            let mut ansi = String::new();
+            progress.write_ansi(&mut ansi).unwrap();
+            ansi
+        };
+        assert_eq!(
+            ansi(TaskbarProgress::new(0.25, false)),
+            "\x1b]9;4;1;25\x1b\\"
+        );
+        assert_eq!(
+            ansi(TaskbarProgress::new(2.0, true)),
+            "\x1b]9;4;4;100\x1b\\"
+        );
+        assert_eq!(ansi(TaskbarProgress::Hidden), "\x1b]9;4;0;0\x1b\\");
+    }
+}
@@ -0,0 +1,193 @@
+use core::ops::{Deref, DerefMut};
+use std::io::{self, Write};
+
+use crossterm::{cursor, style, terminal};
+
+use crate::taskbar_progress::TaskbarProgress;
+
+/// Keeps the terminal in raw mode with a hidden cursor, and usually in the alternate screen,
+/// while alive.
+///
+/// The terminal is restored either explicitly with [`TerminalGuard::restore`],
+/// or when the guard is dropped on an early return or a panic.
+pub struct TerminalGuard<W: Write> {
+    writer: W,
+    active: bool,
+    alternate_screen: bool,
+    /// Whether to leave a cleared screen, or line, behind.
+    clear: bool,
+    /// Whether to take the progress off the taskbar.
+    clear_taskbar_progress: bool,
+}
+
+impl<W: Write> TerminalGuard<W> {
+    /// Enters the alternate screen with a hidden cursor and enables raw mode.
+    pub fn enter(mut writer: W) -> io::Result<Self> {
+        crossterm::execute!(
+            writer,
+            terminal::EnterAlternateScreen,
+            cursor::Hide,
+            cursor::MoveTo(0, 0)
+        )?;
+        Self::activate(writer, true)
+    }
+
+    /// Hides the cursor and enables raw mode, drawing on the current line of the normal screen.
+    pub fn enter_inline(mut writer: W) -> io::Result<Self> {
+        crossterm::execute!(writer, cursor::Hide)?;
+        Self::activate(writer, false)
+    }
+
+    fn activate(writer: W, alternate_screen: bool) -> io::Result<Self> {
+        // Create the guard before enabling raw mode, so the screen is restored if that fails
+        let guard = Self {
+            writer,
+            active: true,
+            alternate_screen,
+            clear: false,
+            clear_taskbar_progress: false,
+        };
+        terminal::enable_raw_mode()?;
+        Ok(guard)
+    }
+
+    /// Clears the screen after leaving the alternate screen, or the last drawn line without it,
+    /// when the terminal is restored.
+    pub fn clear_on_restore(&mut self, clear: bool) {
+        self.clear = clear;
+    }
+
+    /// Takes the progress off the taskbar when the terminal is restored.
+    pub fn clear_taskbar_progress_on_restore(&mut self, clear: bool) {
+        self.clear_taskbar_progress = clear;
+    }
+
+    /// Disables raw mode, shows the cursor and leaves the alternate screen.
+    ///
+    /// Without the alternate screen, moves to a new line instead, leaving the last drawn line
+    /// in the scrollback.
+    ///
+    /// Does nothing if the terminal has already been restored.
+    pub fn restore(&mut self) -> io::Result<()> {
+        if !core::mem::replace(&mut self.active, false) {
+            return Ok(());
+        }
+        let raw_mode = terminal::disable_raw_mode();
+        if self.clear_taskbar_progress {
+            crossterm::queue!(self.writer, TaskbarProgress::Hidden)?;
+        }
+        match (self.alternate_screen, self.clear) {
+            (true, false) => {
+                crossterm::execute!(self.writer, cursor::Show, terminal::LeaveAlternateScreen)?
+            }
+            (true, true) => crossterm::execute!(
+                self.writer,
+                cursor::Show,
+                terminal::LeaveAlternateScreen,
+                terminal::Clear(terminal::ClearType::All),
+                cursor::MoveTo(0, 0),
+            )?,
+            (false, false) => crossterm::execute!(self.writer, cursor::Show, style::Print("\r\n"))?,
+            (false, true) => crossterm::execute!(
+                self.writer,
+                cursor::Show,
+                cursor::MoveToColumn(0),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+            )?,
+        }
+        raw_mode
+    }
+}
+
+impl<W: Write> Deref for TerminalGuard<W> {
+    type Target = W;
+
+    fn deref(&self) -> &Self::Target {
+        &self.writer
+    }
+}
+
+impl<W: Write> DerefMut for TerminalGuard<W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.writer
+    }
+}
+
+impl<W: Write> Write for TerminalGuard<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESTORE: &[u8] = b"\x1b[?25h\x1b[?1049l";
+
+    #[test]
+    fn restore_on_early_return() {
+        let mut output = Vec::new();
+        let result = (|| -> io::Result<()> {
+            let _guard = TerminalGuard::enter(&mut output)?;
+            Err(io::Error::other("forced failure"))
+        })();
+
+        assert!(result.is_err());
+        assert!(output.ends_with(RESTORE));
+    }
+
+    #[test]
+    fn restore_inline_on_a_new_line() {
+        let mut output = Vec::new();
+        let result = (|| -> io::Result<()> {
+            let _guard = TerminalGuard::enter_inline(&mut output)?;
+            Err(io::Error::other("forced failure"))
+        })();
+
+        assert!(result.is_err());
+        assert!(output.starts_with(b"\x1b[?25l"));
+        assert!(output.ends_with(b"\x1b[?25h\r\n"));
+    }
+
+    #[test]
+    fn clear_the_inline_line() {
+        let mut output = Vec::new();
+        // Raw mode can't be enabled without a terminal, which restores the guard right away
+        let Ok(mut guard) = TerminalGuard::enter_inline(&mut output) else {
+            return;
+        };
+        guard.clear_on_restore(true);
+        guard.restore().unwrap();
+        drop(guard);
+
+        assert!(output.ends_with(b"\x1b[?25h\x1b[1G\x1b[2K"));
+    }
+
+    #[test]
+    fn restore_only_once() {
+        let mut output = Vec::new();
+        if let Ok(mut guard) = TerminalGuard::enter(&mut output) {
+            guard.restore().unwrap();
+        }
+
+        assert!(output.ends_with(RESTORE));
+        assert_eq!(
+            output
+                .windows(RESTORE.len())
+                .filter(|window| *window == RESTORE)
+                .count(),
+            1
+        );
+    }
+}
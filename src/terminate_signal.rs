@@ -0,0 +1,29 @@
+use std::io;
+
+/// SIGTERM, as sent by service managers to stop the process.
+///
+/// Listening replaces the default handler, which would end the process without restoring the
+/// terminal. On platforms without SIGTERM, the signal never arrives.
+pub struct TerminateSignal {
+    #[cfg(unix)]
+    signal: tokio::signal::unix::Signal,
+}
+
+impl TerminateSignal {
+    /// Starts listening for the signal, which needs a running Tokio runtime.
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            signal: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+        })
+    }
+
+    /// Waits for the signal to arrive.
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if self.signal.recv().await.is_some() {
+            return;
+        }
+        core::future::pending().await
+    }
+}